@@ -7,8 +7,9 @@ use goose::agents::Agent;
 use goose::agents::{extension::Envs, ExtensionConfig};
 use goose::config::declarative_providers::{create_custom_provider, remove_custom_provider};
 use goose::config::extensions::{
-    get_all_extension_names, get_all_extensions, get_enabled_extensions, get_extension_by_name,
-    name_to_key, remove_extension, set_extension, set_extension_enabled,
+    ensure_default_extension_enabled, get_all_extension_names, get_all_extensions,
+    get_enabled_extensions, get_extension_by_name, name_to_key, remove_extension, set_extension,
+    set_extension_enabled, ExtensionError,
 };
 use goose::config::permission::PermissionLevel;
 use goose::config::{Config, ConfigError, ExperimentManager, ExtensionEntry, PermissionManager};
@@ -102,8 +103,11 @@ pub async fn handle_configure() -> Result<(), Box<dyn Error>> {
                         );
                         // Since we are setting up for the first time, we'll also enable the developer system
                         // This operation is best-effort and errors are ignored
-                        set_extension(ExtensionEntry {
+                        let _ = set_extension(ExtensionEntry {
                             enabled: true,
+                            load_order: None,
+                            updated_at: None,
+                            disabled_tools: Vec::new(),
                             config: ExtensionConfig::default(),
                         });
                     }
@@ -682,10 +686,14 @@ pub fn toggle_extensions_dialog() -> Result<(), Box<dyn Error>> {
 
     // Update enabled status for each extension
     for name in extension_status.iter().map(|(name, _)| name) {
-        set_extension_enabled(
+        match set_extension_enabled(
             &name_to_key(name),
             selected.iter().any(|s| s.as_str() == name),
-        );
+        ) {
+            // Only configured for this project, which this dialog doesn't write.
+            Err(ExtensionError::NotFound(_)) => {}
+            result => result?,
+        }
     }
 
     cliclack::outro("Extension settings updated successfully")?;
@@ -770,6 +778,9 @@ pub fn configure_extensions_dialog() -> Result<(), Box<dyn Error>> {
 
             set_extension(ExtensionEntry {
                 enabled: true,
+                load_order: None,
+                updated_at: None,
+                disabled_tools: Vec::new(),
                 config: ExtensionConfig::Builtin {
                     name: extension.clone(),
                     display_name: Some(display_name),
@@ -778,7 +789,7 @@ pub fn configure_extensions_dialog() -> Result<(), Box<dyn Error>> {
                     description,
                     available_tools: Vec::new(),
                 },
-            });
+            })?;
 
             cliclack::outro(format!("Enabled {} extension", style(extension).green()))?;
         }
@@ -868,6 +879,9 @@ pub fn configure_extensions_dialog() -> Result<(), Box<dyn Error>> {
 
             set_extension(ExtensionEntry {
                 enabled: true,
+                load_order: None,
+                updated_at: None,
+                disabled_tools: Vec::new(),
                 config: ExtensionConfig::Stdio {
                     name: name.clone(),
                     cmd,
@@ -879,7 +893,7 @@ pub fn configure_extensions_dialog() -> Result<(), Box<dyn Error>> {
                     bundled: None,
                     available_tools: Vec::new(),
                 },
-            });
+            })?;
 
             cliclack::outro(format!("Added {} extension", style(name).green()))?;
         }
@@ -964,6 +978,9 @@ pub fn configure_extensions_dialog() -> Result<(), Box<dyn Error>> {
 
             set_extension(ExtensionEntry {
                 enabled: true,
+                load_order: None,
+                updated_at: None,
+                disabled_tools: Vec::new(),
                 config: ExtensionConfig::Sse {
                     name: name.clone(),
                     uri,
@@ -974,7 +991,7 @@ pub fn configure_extensions_dialog() -> Result<(), Box<dyn Error>> {
                     bundled: None,
                     available_tools: Vec::new(),
                 },
-            });
+            })?;
 
             cliclack::outro(format!("Added {} extension", style(name).green()))?;
         }
@@ -1084,6 +1101,9 @@ pub fn configure_extensions_dialog() -> Result<(), Box<dyn Error>> {
 
             set_extension(ExtensionEntry {
                 enabled: true,
+                load_order: None,
+                updated_at: None,
+                disabled_tools: Vec::new(),
                 config: ExtensionConfig::StreamableHttp {
                     name: name.clone(),
                     uri,
@@ -1095,7 +1115,7 @@ pub fn configure_extensions_dialog() -> Result<(), Box<dyn Error>> {
                     bundled: None,
                     available_tools: Vec::new(),
                 },
-            });
+            })?;
 
             cliclack::outro(format!("Added {} extension", style(name).green()))?;
         }
@@ -1151,7 +1171,7 @@ pub fn remove_extension_dialog() -> Result<(), Box<dyn Error>> {
         .interact()?;
 
     for name in selected {
-        remove_extension(&name_to_key(name));
+        remove_extension(&name_to_key(name))?;
         let mut permission_manager = PermissionManager::default();
         permission_manager.remove_extension(&name_to_key(name));
         cliclack::outro(format!("Removed {} extension", style(name).green()))?;
@@ -1700,25 +1720,7 @@ pub async fn handle_openrouter_auth() -> Result<(), Box<dyn Error>> {
                             println!("✓ Configuration test passed!");
 
                             // Enable the developer extension by default if not already enabled
-                            let entries = get_all_extensions();
-                            let has_developer = entries
-                                .iter()
-                                .any(|e| e.config.name() == "developer" && e.enabled);
-
-                            if !has_developer {
-                                set_extension(ExtensionEntry {
-                                    enabled: true,
-                                    config: ExtensionConfig::Builtin {
-                                        name: "developer".to_string(),
-                                        display_name: Some(
-                                            goose::config::DEFAULT_DISPLAY_NAME.to_string(),
-                                        ),
-                                        timeout: Some(goose::config::DEFAULT_EXTENSION_TIMEOUT),
-                                        bundled: Some(true),
-                                        description: "Developer extension".to_string(),
-                                        available_tools: Vec::new(),
-                                    },
-                                });
+                            if ensure_default_extension_enabled()? {
                                 println!("✓ Developer extension enabled");
                             }
 
@@ -1799,25 +1801,7 @@ pub async fn handle_tetrate_auth() -> Result<(), Box<dyn Error>> {
                             println!("✓ Configuration test passed!");
 
                             // Enable the developer extension by default if not already enabled
-                            let entries = get_all_extensions();
-                            let has_developer = entries
-                                .iter()
-                                .any(|e| e.config.name() == "developer" && e.enabled);
-
-                            if !has_developer {
-                                set_extension(ExtensionEntry {
-                                    enabled: true,
-                                    config: ExtensionConfig::Builtin {
-                                        name: "developer".to_string(),
-                                        display_name: Some(
-                                            goose::config::DEFAULT_DISPLAY_NAME.to_string(),
-                                        ),
-                                        timeout: Some(goose::config::DEFAULT_EXTENSION_TIMEOUT),
-                                        bundled: Some(true),
-                                        description: "Developer extension".to_string(),
-                                        available_tools: Vec::new(),
-                                    },
-                                });
+                            if ensure_default_extension_enabled()? {
                                 println!("✓ Developer extension enabled");
                             }
 
@@ -180,12 +180,22 @@ fn check_missing_extensions_or_exit(saved_extensions: &[ExtensionConfig]) {
             process::exit(0);
         }
 
-        missing.into_iter().for_each(|config| {
-            set_extension(ExtensionEntry {
+        for config in missing {
+            let name = config.name();
+            if let Err(e) = set_extension(ExtensionEntry {
                 enabled: true,
+                load_order: None,
+                updated_at: None,
+                disabled_tools: Vec::new(),
                 config,
-            });
-        });
+            }) {
+                eprintln!(
+                    "{}",
+                    style(format!("Failed to re-add extension {}: {}", name, e)).red()
+                );
+                process::exit(1);
+            }
+        }
     }
 }
 
@@ -209,8 +209,12 @@ pub async fn add_extension(
 
     goose::config::set_extension(ExtensionEntry {
         enabled: extension_query.enabled,
+        load_order: None,
+        updated_at: None,
+        disabled_tools: Vec::new(),
         config: extension_query.config,
-    });
+    })
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
     if is_update {
         Ok(Json(format!("Updated extension {}", extension_query.name)))
@@ -230,7 +234,7 @@ pub async fn add_extension(
 )]
 pub async fn remove_extension(Path(name): Path<String>) -> Result<Json<String>, StatusCode> {
     let key = goose::config::extensions::name_to_key(&name);
-    goose::config::remove_extension(&key);
+    goose::config::remove_extension(&key).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     Ok(Json(format!("Removed extension {}", name)))
 }
 
@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use goose::agents::extension::Envs;
+use goose::config::extensions::{ExtensionEntry, ExtensionIndex};
+use goose::config::ExtensionConfig;
+
+const EXTENSION_COUNT: usize = 500;
+
+fn build_extensions() -> HashMap<String, ExtensionEntry> {
+    (0..EXTENSION_COUNT)
+        .map(|i| {
+            let config = ExtensionConfig::Stdio {
+                name: format!("extension-{i}"),
+                description: String::new(),
+                cmd: "cmd".to_string(),
+                args: Vec::new(),
+                envs: Envs::default(),
+                env_keys: Vec::new(),
+                timeout: Some(300),
+                bundled: None,
+                available_tools: vec![format!("tool-{i}"), format!("shared-{}", i % 10)],
+            };
+            (
+                config.key(),
+                ExtensionEntry {
+                    enabled: true,
+                    load_order: None,
+                    updated_at: None,
+                    disabled_tools: Vec::new(),
+                    config,
+                },
+            )
+        })
+        .collect()
+}
+
+// The lookups the plain accessors perform: a scan of the whole map per query.
+fn scan_by_name<'a>(
+    extensions: &'a HashMap<String, ExtensionEntry>,
+    name: &str,
+) -> Option<&'a String> {
+    extensions
+        .iter()
+        .filter(|(_, entry)| entry.config.name() == name)
+        .map(|(key, _)| key)
+        .min()
+}
+
+fn scan_by_tool(extensions: &HashMap<String, ExtensionEntry>, tool: &str) -> Vec<String> {
+    let mut keys: Vec<String> = extensions
+        .iter()
+        .filter(|(_, entry)| entry.config.available_tools().iter().any(|t| t == tool))
+        .map(|(key, _)| key.clone())
+        .collect();
+    keys.sort();
+    keys
+}
+
+fn bench_extension_lookups(c: &mut Criterion) {
+    let extensions = build_extensions();
+    let index = ExtensionIndex::from_extensions(extensions.clone());
+
+    let mut group = c.benchmark_group("extension_lookup_500");
+    group.bench_function("name_scan", |b| {
+        b.iter(|| scan_by_name(&extensions, black_box("extension-499")))
+    });
+    group.bench_function("name_index", |b| {
+        b.iter(|| index.key_for_name(black_box("extension-499")))
+    });
+    group.bench_function("tool_scan", |b| {
+        b.iter(|| scan_by_tool(&extensions, black_box("shared-7")))
+    });
+    group.bench_function("tool_index", |b| {
+        b.iter(|| index.keys_for_tool(black_box("shared-7")))
+    });
+    group.finish();
+
+    c.bench_function("extension_index_build_500", |b| {
+        b.iter(|| ExtensionIndex::from_extensions(black_box(extensions.clone())))
+    });
+}
+
+criterion_group!(benches, bench_extension_lookups);
+criterion_main!(benches);
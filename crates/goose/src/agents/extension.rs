@@ -309,6 +309,16 @@ pub enum ExtensionConfig {
         #[serde(default)]
         available_tools: Vec<String>,
     },
+    /// Placeholder for an extension that is referenced (e.g. by a group) but not configured
+    /// yet. It is never started; UIs should offer to configure it.
+    #[serde(rename = "unresolved")]
+    Unresolved {
+        /// The name used to identify this extension
+        name: String,
+        description: String,
+        #[serde(default)]
+        available_tools: Vec<String>,
+    },
 }
 
 impl Default for ExtensionConfig {
@@ -392,6 +402,14 @@ impl ExtensionConfig {
         }
     }
 
+    pub fn unresolved<S: Into<String>>(name: S) -> Self {
+        Self::Unresolved {
+            name: name.into(),
+            description: String::new(),
+            available_tools: Vec::new(),
+        }
+    }
+
     pub fn with_args<I, S>(self, args: I) -> Self
     where
         I: IntoIterator<Item = S>,
@@ -438,13 +456,28 @@ impl ExtensionConfig {
             Self::Platform { name, .. } => name,
             Self::Frontend { name, .. } => name,
             Self::InlinePython { name, .. } => name,
+            Self::Unresolved { name, .. } => name,
         }
         .to_string()
     }
 
-    /// Check if a tool should be available to the LLM
-    pub fn is_tool_available(&self, tool_name: &str) -> bool {
-        let available_tools = match self {
+    /// Set the extension name regardless of variant
+    pub fn set_name<S: Into<String>>(&mut self, new_name: S) {
+        match self {
+            Self::Sse { name, .. }
+            | Self::StreamableHttp { name, .. }
+            | Self::Stdio { name, .. }
+            | Self::Builtin { name, .. }
+            | Self::Platform { name, .. }
+            | Self::Frontend { name, .. }
+            | Self::InlinePython { name, .. }
+            | Self::Unresolved { name, .. } => *name = new_name.into(),
+        }
+    }
+
+    /// The tools this extension is restricted to; empty means all of its tools
+    pub fn available_tools(&self) -> &[String] {
+        match self {
             Self::Sse {
                 available_tools, ..
             }
@@ -465,12 +498,20 @@ impl ExtensionConfig {
             }
             | Self::Frontend {
                 available_tools, ..
+            }
+            | Self::Unresolved {
+                available_tools, ..
             } => available_tools,
-        };
+        }
+    }
+
+    /// Check if a tool should be available to the LLM
+    pub fn is_tool_available(&self, tool_name: &str) -> bool {
+        let available_tools = self.available_tools();
 
         // If no tools are specified, all tools are available
         // If tools are specified, only those tools are available
-        available_tools.is_empty() || available_tools.contains(&tool_name.to_string())
+        available_tools.is_empty() || available_tools.iter().any(|tool| tool == tool_name)
     }
 }
 
@@ -494,6 +535,7 @@ impl std::fmt::Display for ExtensionConfig {
             ExtensionConfig::InlinePython { name, code, .. } => {
                 write!(f, "InlinePython({}: {} chars)", name, code.len())
             }
+            ExtensionConfig::Unresolved { name, .. } => write!(f, "Unresolved({})", name),
         }
     }
 }
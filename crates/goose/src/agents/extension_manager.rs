@@ -47,12 +47,20 @@ type McpClientBox = Arc<Mutex<Box<dyn McpClientTrait>>>;
 
 struct Extension {
     pub config: ExtensionConfig,
+    /// Tools hidden from the agent even though the extension itself is running.
+    disabled_tools: Vec<String>,
 
     client: McpClientBox,
     server_info: Option<ServerInfo>,
     _temp_dir: Option<tempfile::TempDir>,
 }
 
+/// Whether the agent may see and call `tool_name`: the config lists it as available and the
+/// user hasn't disabled it.
+fn is_tool_enabled(config: &ExtensionConfig, disabled_tools: &[String], tool_name: &str) -> bool {
+    config.is_tool_available(tool_name) && !disabled_tools.iter().any(|tool| tool == tool_name)
+}
+
 impl Extension {
     fn new(
         config: ExtensionConfig,
@@ -63,11 +71,23 @@ impl Extension {
         Self {
             client,
             config,
+            disabled_tools: Vec::new(),
             server_info,
             _temp_dir: temp_dir,
         }
     }
 
+    fn is_tool_enabled(&self, tool_name: &str) -> bool {
+        is_tool_enabled(&self.config, &self.disabled_tools, tool_name)
+    }
+
+    /// An owned `is_tool_enabled`, for filtering tool lists off the extensions lock.
+    fn tool_filter(&self) -> impl Fn(&str) -> bool + Send + 'static {
+        let config = self.config.clone();
+        let disabled_tools = self.disabled_tools.clone();
+        move |tool_name| is_tool_enabled(&config, &disabled_tools, tool_name)
+    }
+
     fn supports_resources(&self) -> bool {
         self.server_info
             .as_ref()
@@ -497,21 +517,40 @@ impl ExtensionManager {
                     "Invalid extension type: Frontend extensions cannot be added as server extensions".to_string()
                 ));
             }
+            ExtensionConfig::Unresolved { name, .. } => {
+                return Err(ExtensionError::ConfigError(format!(
+                    "Extension {} is a placeholder and must be configured before it can start",
+                    name
+                )));
+            }
         };
 
         let server_info = client.get_info().cloned();
         self.add_client(
-            sanitized_name,
+            sanitized_name.clone(),
             config,
             Arc::new(Mutex::new(client)),
             server_info,
             temp_dir,
         )
         .await;
+        self.set_disabled_tools(
+            &sanitized_name,
+            crate::config::extensions::get_disabled_tools(&config_name),
+        )
+        .await;
 
         Ok(())
     }
 
+    /// Hides `tools` of a running extension from the tool list and refuses calls to them,
+    /// replacing any previously disabled set. Does nothing if the extension isn't running.
+    pub async fn set_disabled_tools(&self, name: &str, tools: Vec<String>) {
+        if let Some(extension) = self.extensions.lock().await.get_mut(name) {
+            extension.disabled_tools = tools;
+        }
+    }
+
     pub async fn add_client(
         &self,
         name: String,
@@ -612,11 +651,11 @@ impl ExtensionManager {
                     true
                 }
             })
-            .map(|(name, ext)| (name.clone(), ext.config.clone(), ext.get_client()))
+            .map(|(name, ext)| (name.clone(), ext.tool_filter(), ext.get_client()))
             .collect();
 
         let cancel_token = CancellationToken::default();
-        let client_futures = filtered_clients.into_iter().map(|(name, config, client)| {
+        let client_futures = filtered_clients.into_iter().map(|(name, filter, client)| {
             let cancel_token = cancel_token.clone();
             task::spawn(async move {
                 let mut tools = Vec::new();
@@ -625,9 +664,7 @@ impl ExtensionManager {
 
                 loop {
                     for tool in client_tools.tools {
-                        let is_available = config.is_tool_available(&tool.name);
-
-                        if is_available {
+                        if filter(&tool.name) {
                             tools.push(Tool {
                                 name: format!("{}__{}", name, tool.name).into(),
                                 description: tool.description,
@@ -923,7 +960,7 @@ impl ExtensionManager {
             .to_string();
 
         if let Some(extension) = self.extensions.lock().await.get(&client_name) {
-            if !extension.config.is_tool_available(&tool_name) {
+            if !extension.is_tool_enabled(&tool_name) {
                 return Err(ErrorData::new(
                     ErrorCode::RESOURCE_NOT_FOUND,
                     format!(
@@ -1082,7 +1119,8 @@ impl ExtensionManager {
                     | ExtensionConfig::StreamableHttp { description, .. }
                     | ExtensionConfig::Stdio { description, .. }
                     | ExtensionConfig::Frontend { description, .. }
-                    | ExtensionConfig::InlinePython { description, .. } => description,
+                    | ExtensionConfig::InlinePython { description, .. }
+                    | ExtensionConfig::Unresolved { description, .. } => description,
                 };
                 disabled_extensions.push(format!("- {} - {}", config.name(), description));
             }
@@ -1538,4 +1576,46 @@ mod tests {
 
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_disabled_tools_hidden_while_extension_runs() {
+        let extension_manager = ExtensionManager::new();
+
+        extension_manager
+            .add_mock_extension(
+                "test_extension".to_string(),
+                Arc::new(Mutex::new(Box::new(MockClient {}))),
+            )
+            .await;
+        extension_manager
+            .set_disabled_tools("test_extension", vec!["hidden_tool".to_string()])
+            .await;
+
+        let tools = extension_manager.get_prefixed_tools(None).await.unwrap();
+        let tool_names: Vec<String> = tools.iter().map(|t| t.name.to_string()).collect();
+        assert_eq!(tool_names.len(), 2);
+        assert!(!tool_names
+            .iter()
+            .any(|name| name == "test_extension__hidden_tool"));
+
+        let disabled_tool_call = CallToolRequestParam {
+            name: "test_extension__hidden_tool".to_string().into(),
+            arguments: Some(object!({})),
+        };
+        let result = extension_manager
+            .dispatch_tool_call(disabled_tool_call, CancellationToken::default())
+            .await;
+        let err = result.err().expect("disabled tool should not be callable");
+        let tool_err = err.downcast_ref::<ErrorData>().expect("Expected ErrorData");
+        assert_eq!(tool_err.code, ErrorCode::RESOURCE_NOT_FOUND);
+
+        let enabled_tool_call = CallToolRequestParam {
+            name: "test_extension__tool".to_string().into(),
+            arguments: Some(object!({})),
+        };
+        assert!(extension_manager
+            .dispatch_tool_call(enabled_tool_call, CancellationToken::default())
+            .await
+            .is_ok());
+    }
 }
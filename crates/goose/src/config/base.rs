@@ -633,6 +633,33 @@ impl Config {
         self.save_values(values)
     }
 
+    /// Set or delete several configuration values in a single write.
+    ///
+    /// Each `Some` value is stored under its key and each `None` removes the key. Either
+    /// all of the changes reach the config file or none of them do.
+    ///
+    /// # Errors
+    ///
+    /// Returns a ConfigError if:
+    /// - There is an error reading or writing the config file
+    /// - There is an error serializing a value
+    pub fn set_params<'a>(
+        &self,
+        params: impl IntoIterator<Item = (&'a str, Option<Value>)>,
+    ) -> Result<(), ConfigError> {
+        let _guard = self.guard.lock().unwrap();
+
+        let mut values = self.load_values()?;
+        for (key, value) in params {
+            match value {
+                Some(value) => values.insert(key.to_string(), value),
+                None => values.remove(key),
+            };
+        }
+
+        self.save_values(values)
+    }
+
     /// Get a secret value.
     ///
     /// This will attempt to get the value from:
@@ -848,6 +875,25 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_set_params_writes_and_deletes_together() -> Result<(), ConfigError> {
+        let temp_file = NamedTempFile::new().unwrap();
+        let config = Config::new(temp_file.path(), TEST_KEYRING_SERVICE)?;
+        config.set_param("stale_key", Value::String("old".to_string()))?;
+
+        config.set_params([
+            ("fresh_key", Some(Value::String("new".to_string()))),
+            ("stale_key", None),
+        ])?;
+
+        let value: String = config.get_param("fresh_key")?;
+        assert_eq!(value, "new");
+        let result: Result<String, ConfigError> = config.get_param("stale_key");
+        assert!(matches!(result, Err(ConfigError::NotFound(_))));
+
+        Ok(())
+    }
+
     #[test]
     fn test_missing_value() {
         let temp_file = NamedTempFile::new().unwrap();
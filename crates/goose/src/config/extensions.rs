@@ -1,33 +1,791 @@
-use super::base::Config;
-use crate::agents::extension::PLATFORM_EXTENSIONS;
+use super::base::{Config, ConfigError};
+use crate::agents::extension::{Envs, PlatformExtensionDef, PLATFORM_EXTENSIONS};
 use crate::agents::ExtensionConfig;
+use lru::LruCache;
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Instant;
+use thiserror::Error;
+use tokio::task::JoinError;
 use tracing::warn;
 use utoipa::ToSchema;
 
 pub const DEFAULT_EXTENSION: &str = "developer";
 pub const DEFAULT_EXTENSION_TIMEOUT: u64 = 300;
+pub const MAX_EXTENSION_TIMEOUT: u64 = 60 * 60;
 pub const DEFAULT_EXTENSION_DESCRIPTION: &str = "";
 pub const DEFAULT_DISPLAY_NAME: &str = "Developer";
 const EXTENSIONS_CONFIG_KEY: &str = "extensions";
+const EXTENSION_GROUPS_CONFIG_KEY: &str = "extension_groups";
+const MAX_EXTENSIONS_CONFIG_KEY: &str = "max_extensions";
+const DEFAULT_EXTENSION_DESCRIPTION_CONFIG_KEY: &str = "default_extension_description";
+const CASE_SENSITIVE_KEYS_CONFIG_KEY: &str = "case_sensitive_extension_keys";
+const DEFAULT_EXTENSION_KEY_CONFIG_KEY: &str = "default_extension";
+const INJECT_PLATFORM_EXTENSIONS_CONFIG_KEY: &str = "inject_platform_extensions";
 
 #[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
 pub struct ExtensionEntry {
     pub enabled: bool,
+    /// Position in the startup sequence; lower values load first, unset loads last.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub load_order: Option<i32>,
+    /// Unix seconds of the last write through one of the mutators.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<i64>,
+    /// Tools to hide from the agent while the extension itself stays enabled.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disabled_tools: Vec<String>,
     #[serde(flatten)]
     pub config: ExtensionConfig,
 }
 
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, ToSchema)]
+pub struct ExtensionGroup {
+    pub name: String,
+    #[serde(default)]
+    pub extension_keys: Vec<String>,
+    /// Keys of groups that must be disabled whenever this group is enabled.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclusive_with: Vec<String>,
+    /// Glob patterns (`*`, `?`) over extension keys, expanded against the current
+    /// extensions whenever the group is evaluated, in addition to `extension_keys`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub patterns: Vec<String>,
+    #[serde(default, skip_serializing_if = "GroupToggleMode::is_mutate_members")]
+    pub toggle_mode: GroupToggleMode,
+    /// The group's own switch, only consulted in `GroupToggleMode::Mask`.
+    #[serde(
+        default = "default_group_enabled",
+        skip_serializing_if = "is_group_enabled"
+    )]
+    pub enabled: bool,
+    /// Members are only ever enabled or disabled together; toggling one through
+    /// `set_extension_enabled` toggles them all.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub all_or_nothing: bool,
+}
+
+/// How enabling or disabling a group affects its members.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupToggleMode {
+    /// Each member's own `enabled` flag is flipped.
+    #[default]
+    MutateMembers,
+    /// Only the group's `enabled` flag is flipped. While it is off the members are masked at
+    /// query time, and they return to their own states when it is switched back on.
+    Mask,
+}
+
+impl GroupToggleMode {
+    fn is_mutate_members(&self) -> bool {
+        *self == GroupToggleMode::MutateMembers
+    }
+}
+
+fn default_group_enabled() -> bool {
+    true
+}
+
+fn is_group_enabled(enabled: &bool) -> bool {
+    *enabled
+}
+
+impl ExtensionGroup {
+    pub fn new<S: Into<String>>(name: S, extension_keys: Vec<String>) -> Self {
+        Self {
+            name: name.into(),
+            extension_keys,
+            exclusive_with: Vec::new(),
+            patterns: Vec::new(),
+            toggle_mode: GroupToggleMode::default(),
+            enabled: true,
+            all_or_nothing: false,
+        }
+    }
+
+    pub fn with_all_or_nothing(mut self, all_or_nothing: bool) -> Self {
+        self.all_or_nothing = all_or_nothing;
+        self
+    }
+
+    pub fn with_toggle_mode(mut self, toggle_mode: GroupToggleMode) -> Self {
+        self.toggle_mode = toggle_mode;
+        self
+    }
+
+    /// Whether this is a switched-off `Mask` group, hiding its members from queries.
+    pub fn is_masking(&self) -> bool {
+        self.toggle_mode == GroupToggleMode::Mask && !self.enabled
+    }
+
+    pub fn with_patterns(mut self, patterns: Vec<String>) -> Self {
+        self.patterns = patterns;
+        self
+    }
+
+    /// Group names or keys are both accepted; they are stored as keys via `name_to_key`.
+    pub fn with_exclusive_with(mut self, group_keys: Vec<String>) -> Self {
+        self.exclusive_with = group_keys.iter().map(|key| name_to_key(key)).collect();
+        self
+    }
+
+    pub fn key(&self) -> String {
+        name_to_key(&self.name)
+    }
+
+    /// Whether `key` is a member, either explicitly or through one of the patterns.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.extension_keys.iter().any(|k| k == key)
+            || self.patterns.iter().any(|p| glob_matches(p, key))
+    }
+
+    /// The explicit members followed by every extension key matched by a pattern, without
+    /// duplicates. Pattern matches are sorted so the result is deterministic.
+    pub fn resolve_members(&self, extensions: &HashMap<String, ExtensionEntry>) -> Vec<String> {
+        let mut members: Vec<String> = Vec::with_capacity(self.extension_keys.len());
+        for key in &self.extension_keys {
+            if !members.contains(key) {
+                members.push(key.clone());
+            }
+        }
+        if self.patterns.is_empty() {
+            return members;
+        }
+
+        let mut matched: Vec<&String> = extensions
+            .keys()
+            .filter(|key| !members.contains(key))
+            .filter(|key| self.patterns.iter().any(|p| glob_matches(p, key)))
+            .collect();
+        matched.sort();
+        members.extend(matched.into_iter().cloned());
+        members
+    }
+}
+
+fn glob_matches(pattern: &str, key: &str) -> bool {
+    let regex = format!(
+        "^{}$",
+        regex::escape(pattern)
+            .replace(r"\*", ".*")
+            .replace(r"\?", ".")
+    );
+    regex::Regex::new(&regex)
+        .map(|re| re.is_match(key))
+        .unwrap_or(false)
+}
+
+/// Aggregate enablement of a group's members. A group with members missing from the
+/// extensions map is never fully `Enabled` or `Disabled`, so it reports `Mixed`.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExtensionGroupState {
+    Enabled,
+    Disabled,
+    Mixed,
+}
+
+/// Why a change to the stored extensions or groups could not be persisted. `key` is the
+/// config key (or project overlay path) being written.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum SaveError {
+    #[error("failed to serialize {key}: {error}")]
+    Serialize { key: String, error: String },
+    #[error("failed to write {key}: {error}")]
+    Write { key: String, error: String },
+    #[error("no project overlay is configured")]
+    NoProjectOverlay,
+}
+
+#[derive(Error, Debug)]
+pub enum ExtensionGroupError {
+    #[error("extension group not found: {0}")]
+    NotFound(String),
+    #[error("extension group already exists: {0}")]
+    AlreadyExists(String),
+    #[error("unknown extensions: {}", .0.join(", "))]
+    UnknownExtensions(Vec<String>),
+    #[error("extension group key {0} is already used by an extension")]
+    KeyConflict(String),
+    #[error("extension group name {0:?} has no characters to derive a key from")]
+    EmptyKey(String),
+    #[error(transparent)]
+    Save(#[from] SaveError),
+}
+
+/// Where a key is in use. Extensions and groups live in separate maps, but UIs address both
+/// by key, so a key used by both is ambiguous.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyConflict {
+    None,
+    Extension,
+    Group,
+    Both,
+}
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ExtensionValidationError {
+    #[error("invalid uri {uri:?}: {reason}")]
+    InvalidUri { uri: String, reason: String },
+    #[error("header {0:?} has an empty name or value")]
+    EmptyHeader(String),
+    #[error("timeout must be between 1 and {max} seconds, got {0}", max = MAX_EXTENSION_TIMEOUT)]
+    InvalidTimeout(u64),
+}
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error(
+    "extensions config was modified externally (expected fingerprint {expected:016x}, found {actual:016x})"
+)]
+pub struct ConfigConflictError {
+    pub expected: u64,
+    pub actual: u64,
+}
+
+#[derive(Error, Debug)]
+pub enum ExtensionError {
+    #[error("extension not found: {0}")]
+    NotFound(String),
+    #[error("extension already exists: {0}")]
+    AlreadyExists(String),
+    #[error("invalid extension config: {0}")]
+    Invalid(String),
+    #[error("no extension config change to undo")]
+    NothingToUndo,
+    #[error("cannot add {key}: the config already holds the maximum of {limit} extensions")]
+    CapacityExceeded { key: String, limit: usize },
+    #[error("{key}: {name} is redacted; supply the real value before importing")]
+    RedactedSecret { key: String, name: String },
+    #[error("invalid extensions: {}", .0.join(", "))]
+    InvalidEntries(Vec<String>),
+    #[error("extension key {0} is already used by an extension group")]
+    KeyConflict(String),
+    #[error("extension name {0:?} has no characters to derive a key from")]
+    EmptyKey(String),
+    #[error(
+        "cannot enable {key}: its all_or_nothing group needs {}, which are not configured yet",
+        .placeholders.join(", ")
+    )]
+    PlaceholderMembers {
+        key: String,
+        placeholders: Vec<String>,
+    },
+    #[error(transparent)]
+    Validation(#[from] ExtensionValidationError),
+    #[error(transparent)]
+    Conflict(#[from] ConfigConflictError),
+    #[error(transparent)]
+    Save(#[from] SaveError),
+}
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ImportError {
+    #[error("invalid MCP manifest: {0}")]
+    InvalidManifest(String),
+    #[error(transparent)]
+    Save(#[from] SaveError),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnknownField {
+    pub key: String,
+    pub field: String,
+}
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum StrictParseError {
+    #[error("failed to load extensions config: {0}")]
+    Load(String),
+    #[error("expected an object of extensions")]
+    NotAnObject,
+    #[error("extension {key} is malformed: {error}")]
+    Malformed { key: String, error: String },
+    #[error("unknown extension fields: {}", format_unknown_fields(.0))]
+    UnknownFields(Vec<UnknownField>),
+}
+
+/// An entry the lenient loader would have skipped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MalformedEntry {
+    pub key: String,
+    pub error: String,
+    pub bad_json: String,
+}
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum LoadError {
+    #[error("failed to read {location}: {error}")]
+    Read { location: String, error: String },
+    #[error("expected an object of extensions in {0}")]
+    NotAnObject(String),
+    #[error("malformed extensions: {}", .0.iter().map(|m| m.key.as_str()).collect::<Vec<_>>().join(", "))]
+    Malformed(Vec<MalformedEntry>),
+}
+
+fn format_unknown_fields(fields: &[UnknownField]) -> String {
+    fields
+        .iter()
+        .map(|f| format!("{}.{}", f.key, f.field))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+thread_local! {
+    // Malformed entries skipped by the loaders on this thread, reported on the load spans.
+    static MALFORMED_SKIPPED: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+fn count_malformed_skipped() {
+    MALFORMED_SKIPPED.with(|count| count.set(count.get() + 1));
+}
+
+fn take_malformed_skipped() -> usize {
+    MALFORMED_SKIPPED.with(|count| count.replace(0))
+}
+
+/// Records the stable `entries` and `elapsed_ms` fields shared by the config I/O spans.
+fn record_io_span(start: Instant, entries: usize) {
+    let span = tracing::Span::current();
+    span.record("entries", entries);
+    span.record("elapsed_ms", start.elapsed().as_secs_f64() * 1000.0);
+}
+
+#[cfg(test)]
+thread_local! {
+    static TEST_NOW: std::cell::Cell<Option<i64>> = const { std::cell::Cell::new(None) };
+}
+
+/// Current unix time used to stamp `updated_at`; tests can pin it with `set_test_now`.
+fn now_unix() -> i64 {
+    #[cfg(test)]
+    {
+        if let Some(now) = TEST_NOW.with(|now| now.get()) {
+            return now;
+        }
+    }
+    chrono::Utc::now().timestamp()
+}
+
+#[cfg(test)]
+fn set_test_now(now: Option<i64>) {
+    TEST_NOW.with(|cell| cell.set(now));
+}
+
+// Read once so every key derived during the process agrees, whatever the config later says.
+static CASE_SENSITIVE_KEYS: Lazy<bool> = Lazy::new(|| {
+    // Unit tests cover both modes through `key_from_name` and must not read the user's config.
+    if cfg!(test) {
+        return false;
+    }
+    Config::global()
+        .get_param::<bool>(CASE_SENSITIVE_KEYS_CONFIG_KEY)
+        .unwrap_or(false)
+});
+
+/// Derives the key an extension or group is stored under: the name with whitespace removed,
+/// lowercased unless `case_sensitive_extension_keys` is set. The setting is read once per
+/// process; switching it on an existing config requires migrating the stored keys, since
+/// entries saved under lowercased keys won't be found by their case-preserving names.
 pub fn name_to_key(name: &str) -> String {
-    name.chars()
-        .filter(|c| !c.is_whitespace())
-        .collect::<String>()
-        .to_lowercase()
+    key_from_name(name, *CASE_SENSITIVE_KEYS)
 }
 
-fn get_extensions_map() -> HashMap<String, ExtensionEntry> {
+/// Whether `name` yields a non-empty key. Names made only of whitespace would be stored
+/// under `""`, which nothing can address.
+pub fn is_valid_extension_name(name: &str) -> bool {
+    name.chars().any(|c| !c.is_whitespace())
+}
+
+fn key_from_name(name: &str, case_sensitive: bool) -> String {
+    let key: String = name.chars().filter(|c| !c.is_whitespace()).collect();
+    if case_sensitive {
+        key
+    } else {
+        key.to_lowercase()
+    }
+}
+
+/// Which config file an extension entry is read from or written to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExtensionLayer {
+    /// The user's global goose config.
+    Global,
+    /// The `.goose/extensions.yaml` of the project set via `with_project_overlay`.
+    Project,
+}
+
+pub const PROJECT_EXTENSIONS_FILE: &str = ".goose/extensions.yaml";
+
+static PROJECT_OVERLAY: Lazy<RwLock<Option<PathBuf>>> = Lazy::new(|| RwLock::new(None));
+
+/// Layers the extensions in `<project_dir>/.goose/extensions.yaml` on top of the global
+/// config for every subsequent read. Project entries win on key conflicts; writes keep
+/// targeting the global config unless `ExtensionLayer::Project` is requested.
+pub fn with_project_overlay<P: AsRef<Path>>(project_dir: P) {
+    let path = project_dir.as_ref().join(PROJECT_EXTENSIONS_FILE);
+    *PROJECT_OVERLAY.write().unwrap() = Some(path);
+    invalidate_extension_index();
+}
+
+pub fn clear_project_overlay() {
+    *PROJECT_OVERLAY.write().unwrap() = None;
+    invalidate_extension_index();
+}
+
+fn project_overlay_path() -> Option<PathBuf> {
+    PROJECT_OVERLAY.read().unwrap().clone()
+}
+
+/// A session-only change to a stored extension, applied when the config is read.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct RuntimeOverride {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+}
+
+static RUNTIME_OVERRIDES: Lazy<RwLock<HashMap<String, RuntimeOverride>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// Installs per-key overrides on top of the effective config for every subsequent read in
+/// this process, replacing any installed before. They are never written to the config;
+/// overrides for keys that aren't configured are ignored.
+pub fn with_runtime_overrides(overrides: HashMap<String, RuntimeOverride>) {
+    *RUNTIME_OVERRIDES.write().unwrap() = overrides;
+    invalidate_extension_index();
+}
+
+pub fn clear_runtime_overrides() {
+    RUNTIME_OVERRIDES.write().unwrap().clear();
+    invalidate_extension_index();
+}
+
+fn apply_runtime_overrides(
+    extensions: &mut HashMap<String, ExtensionEntry>,
+    overrides: &HashMap<String, RuntimeOverride>,
+) {
+    for (key, over) in overrides {
+        let Some(entry) = extensions.get_mut(key) else {
+            continue;
+        };
+        if let Some(enabled) = over.enabled {
+            entry.enabled = enabled;
+        }
+        if let Some(timeout) = over.timeout {
+            set_extension_timeout(&mut entry.config, timeout);
+        }
+    }
+}
+
+/// The description given to entries that don't have one, configurable so teams can
+/// template it. Falls back to `DEFAULT_EXTENSION_DESCRIPTION`.
+pub fn get_default_extension_description() -> String {
+    Config::global()
+        .get_param::<String>(DEFAULT_EXTENSION_DESCRIPTION_CONFIG_KEY)
+        .unwrap_or_else(|_| DEFAULT_EXTENSION_DESCRIPTION.to_string())
+}
+
+/// The key of the extension goose falls back to, configurable so teams can swap in their
+/// own. Falls back to `DEFAULT_EXTENSION`.
+pub fn get_default_extension_key() -> String {
+    Config::global()
+        .get_param::<String>(DEFAULT_EXTENSION_KEY_CONFIG_KEY)
+        .unwrap_or_else(|_| DEFAULT_EXTENSION.to_string())
+}
+
+/// The entry stored under the default extension key, including any user customization or a
+/// disabled flag. A platform default that isn't stored is only found while
+/// `inject_platform_extensions` is on.
+pub fn get_default_extension_entry() -> Option<ExtensionEntry> {
+    get_extensions_map().remove(&get_default_extension_key())
+}
+
+/// Makes sure the default extension is present and enabled: enables it if it is stored but
+/// disabled, and re-adds it if it is missing and goose ships a definition for its key.
+/// A disabled entry in the project overlay is enabled there, since it would otherwise keep
+/// shadowing the global one. Returns whether anything was written.
+pub fn ensure_default_extension_enabled() -> Result<bool, ExtensionError> {
+    let key = get_default_extension_key();
+    if get_extensions_map()
+        .get(&key)
+        .is_some_and(|entry| entry.enabled)
+    {
+        return Ok(false);
+    }
+
+    let mut project = get_extensions_layer(ExtensionLayer::Project);
+    if let Some(entry) = project.get_mut(&key).filter(|entry| !entry.enabled) {
+        entry.enabled = true;
+        entry.updated_at = Some(now_unix());
+        save_extensions_layer(ExtensionLayer::Project, project)?;
+        return Ok(true);
+    }
+
+    let mut extensions = get_extensions_layer(ExtensionLayer::Global);
+    if extensions.contains_key(&key) {
+        set_extension_enabled(&key, true)?;
+        return Ok(true);
+    }
+    match bundled_default_entry(&key) {
+        Some(mut entry) => {
+            entry.updated_at = Some(now_unix());
+            extensions.insert(key, entry);
+            save_extensions_map(extensions)?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// The enabled entry goose ships for `key`, if it is a platform extension or the built-in
+/// `DEFAULT_EXTENSION`.
+fn bundled_default_entry(key: &str) -> Option<ExtensionEntry> {
+    if let Some(def) = PLATFORM_EXTENSIONS.get(key) {
+        return Some(platform_entry(def, true));
+    }
+    (key == DEFAULT_EXTENSION).then(|| ExtensionEntry {
+        enabled: true,
+        load_order: None,
+        updated_at: None,
+        disabled_tools: Vec::new(),
+        config: ExtensionConfig::Builtin {
+            name: DEFAULT_EXTENSION.to_string(),
+            display_name: Some(DEFAULT_DISPLAY_NAME.to_string()),
+            description: "Developer extension".to_string(),
+            timeout: Some(DEFAULT_EXTENSION_TIMEOUT),
+            bundled: Some(true),
+            available_tools: Vec::new(),
+        },
+    })
+}
+
+pub fn set_default_extension_description<S: Into<String>>(description: S) -> Result<(), SaveError> {
+    Config::global()
+        .set_param(
+            DEFAULT_EXTENSION_DESCRIPTION_CONFIG_KEY,
+            Value::String(description.into()),
+        )
+        .map_err(|e| setting_write_error(DEFAULT_EXTENSION_DESCRIPTION_CONFIG_KEY, e))
+}
+
+fn setting_write_error(key: &str, e: ConfigError) -> SaveError {
+    tracing::debug!("Failed to save {}: {}", key, e);
+    SaveError::Write {
+        key: key.to_string(),
+        error: e.to_string(),
+    }
+}
+
+fn fill_missing_description(v: &mut Value, default_description: &str) {
+    if let Value::Object(ref mut inner) = v {
+        match inner.get("description") {
+            Some(Value::Null) | None => {
+                inner.insert(
+                    "description".to_string(),
+                    Value::String(default_description.to_string()),
+                );
+            }
+            _ => {}
+        }
+    }
+}
+
+fn parse_extensions_value(
+    raw: Value,
+    source: &str,
+    default_description: &str,
+) -> HashMap<String, ExtensionEntry> {
+    match raw {
+        Value::Object(obj) => {
+            let (m, malformed) = parse_extension_entries(obj, default_description);
+            for bad in malformed {
+                count_malformed_skipped();
+                warn!(
+                    extension = %bad.key,
+                    error = %bad.error,
+                    bad_json = %bad.bad_json,
+                    "Skipping malformed extension"
+                );
+            }
+            m
+        }
+        other => {
+            warn!(
+                "Expected object for {}, got {}. Using empty map.",
+                source, other
+            );
+            HashMap::new()
+        }
+    }
+}
+
+fn parse_extension_entries(
+    obj: serde_json::Map<String, Value>,
+    default_description: &str,
+) -> (HashMap<String, ExtensionEntry>, Vec<MalformedEntry>) {
+    let mut m = HashMap::with_capacity(obj.len());
+    let mut malformed = Vec::new();
+    for (k, mut v) in obj {
+        fill_missing_description(&mut v, default_description);
+        match serde_json::from_value::<ExtensionEntry>(v.clone()) {
+            Ok(entry) => {
+                m.insert(k, entry);
+            }
+            Err(err) => {
+                let bad_json = serde_json::to_string(&v)
+                    .unwrap_or_else(|e| format!("<failed to serialize malformed value: {e}>"));
+                malformed.push(MalformedEntry {
+                    key: k,
+                    error: err.to_string(),
+                    bad_json,
+                });
+            }
+        }
+    }
+    (m, malformed)
+}
+
+/// Loads the effective extensions like `get_extensions_map`, but fails on the first sign of
+/// corruption instead of degrading quietly: every malformed entry across the global config and
+/// the project overlay is reported together.
+pub fn try_get_extensions_map() -> Result<HashMap<String, ExtensionEntry>, LoadError> {
+    let default_description = get_default_extension_description();
+    let global = match Config::global().get_param::<Value>(EXTENSIONS_CONFIG_KEY) {
+        Ok(raw) => raw,
+        Err(ConfigError::NotFound(_)) => Value::Object(serde_json::Map::new()),
+        Err(e) => {
+            return Err(LoadError::Read {
+                location: EXTENSIONS_CONFIG_KEY.to_string(),
+                error: e.to_string(),
+            })
+        }
+    };
+
+    let mut malformed = Vec::new();
+    let global = parse_extensions_collecting(
+        global,
+        EXTENSIONS_CONFIG_KEY,
+        &default_description,
+        &mut malformed,
+    )?;
+    let project = match project_overlay_path() {
+        Some(path) if path.exists() => {
+            let location = path.to_string_lossy().to_string();
+            match read_yaml_value(&path) {
+                Ok(Value::Null) => HashMap::new(),
+                Ok(raw) => parse_extensions_collecting(
+                    raw,
+                    &location,
+                    &default_description,
+                    &mut malformed,
+                )?,
+                Err(error) => return Err(LoadError::Read { location, error }),
+            }
+        }
+        _ => HashMap::new(),
+    };
+    if !malformed.is_empty() {
+        return Err(LoadError::Malformed(malformed));
+    }
+
+    Ok(layer_extensions(
+        global,
+        project,
+        &RUNTIME_OVERRIDES.read().unwrap(),
+    ))
+}
+
+fn parse_extensions_collecting(
+    raw: Value,
+    source: &str,
+    default_description: &str,
+    malformed: &mut Vec<MalformedEntry>,
+) -> Result<HashMap<String, ExtensionEntry>, LoadError> {
+    let Value::Object(obj) = raw else {
+        return Err(LoadError::NotAnObject(source.to_string()));
+    };
+    let (extensions, bad) = parse_extension_entries(obj, default_description);
+    malformed.extend(bad);
+    Ok(extensions)
+}
+
+/// Loads the stored extensions like `get_extensions_map`, but fails instead of skipping
+/// malformed entries and reports every field serde would otherwise silently ignore.
+pub fn load_extensions_strict() -> Result<HashMap<String, ExtensionEntry>, StrictParseError> {
+    let raw = match Config::global().get_param::<Value>(EXTENSIONS_CONFIG_KEY) {
+        Ok(raw) => raw,
+        Err(ConfigError::NotFound(_)) => return Ok(HashMap::new()),
+        Err(e) => return Err(StrictParseError::Load(e.to_string())),
+    };
+    let mut extensions = parse_extensions_strict(raw, &get_default_extension_description())?;
+    inject_platform_extensions(&mut extensions);
+    Ok(extensions)
+}
+
+fn parse_extensions_strict(
+    raw: Value,
+    default_description: &str,
+) -> Result<HashMap<String, ExtensionEntry>, StrictParseError> {
+    let Value::Object(obj) = raw else {
+        return Err(StrictParseError::NotAnObject);
+    };
+
+    let mut extensions = HashMap::with_capacity(obj.len());
+    let mut unknown = Vec::new();
+    for (key, mut value) in obj {
+        fill_missing_description(&mut value, default_description);
+        let entry: ExtensionEntry =
+            serde_json::from_value(value.clone()).map_err(|e| StrictParseError::Malformed {
+                key: key.clone(),
+                error: e.to_string(),
+            })?;
+        unknown.extend(
+            unknown_entry_fields(&value, &entry)
+                .into_iter()
+                .map(|field| UnknownField {
+                    key: key.clone(),
+                    field,
+                }),
+        );
+        extensions.insert(key, entry);
+    }
+
+    if !unknown.is_empty() {
+        unknown.sort_by(|a, b| (&a.key, &a.field).cmp(&(&b.key, &b.field)));
+        return Err(StrictParseError::UnknownFields(unknown));
+    }
+    Ok(extensions)
+}
+
+/// The fields `ExtensionEntry` declares itself, outside the flattened config. Some are
+/// skipped when empty, so they can't be recovered from a round trip.
+const ENTRY_FIELDS: &[&str] = &["enabled", "load_order", "updated_at", "disabled_tools"];
+
+/// Fields present in the raw value that are neither declared by `ExtensionEntry` nor survive
+/// a round trip through its flattened config are the ones serde ignored.
+fn unknown_entry_fields(raw: &Value, entry: &ExtensionEntry) -> Vec<String> {
+    let (Value::Object(raw), Ok(Value::Object(known))) = (raw, serde_json::to_value(entry)) else {
+        return Vec::new();
+    };
+    raw.iter()
+        .filter(|(field, value)| {
+            !value.is_null()
+                && !ENTRY_FIELDS.contains(&field.as_str())
+                && !known.contains_key(*field)
+        })
+        .map(|(field, _)| field.clone())
+        .collect()
+}
+
+fn read_global_extensions() -> HashMap<String, ExtensionEntry> {
     let raw: Value = Config::global()
         .get_param::<Value>(EXTENSIONS_CONFIG_KEY)
         .unwrap_or_else(|err| {
@@ -38,129 +796,5604 @@ fn get_extensions_map() -> HashMap<String, ExtensionEntry> {
             Value::Object(serde_json::Map::new())
         });
 
-    let mut extensions_map: HashMap<String, ExtensionEntry> = match raw {
-        Value::Object(obj) => {
-            let mut m = HashMap::with_capacity(obj.len());
-            for (k, mut v) in obj {
-                if let Value::Object(ref mut inner) = v {
-                    match inner.get("description") {
-                        Some(Value::Null) | None => {
-                            inner.insert("description".to_string(), Value::String(String::new()));
-                        }
-                        _ => {}
-                    }
-                }
-                match serde_json::from_value::<ExtensionEntry>(v.clone()) {
-                    Ok(entry) => {
-                        m.insert(k, entry);
-                    }
-                    Err(err) => {
-                        let bad_json = serde_json::to_string(&v).unwrap_or_else(|e| {
-                            format!("<failed to serialize malformed value: {e}>")
-                        });
-                        warn!(
-                            extension = %k,
-                            error = %err,
-                            bad_json = %bad_json,
-                            "Skipping malformed extension"
-                        );
-                    }
-                }
-            }
-            m
+    parse_extensions_value(
+        raw,
+        EXTENSIONS_CONFIG_KEY,
+        &get_default_extension_description(),
+    )
+}
+
+fn read_project_extensions() -> HashMap<String, ExtensionEntry> {
+    match project_overlay_path() {
+        Some(path) if path.exists() => {
+            read_extensions_file(&path, &get_default_extension_description())
         }
-        other => {
+        _ => HashMap::new(),
+    }
+}
+
+fn read_yaml_value(path: &Path) -> Result<Value, String> {
+    std::fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|content| serde_yaml::from_str::<Value>(&content).map_err(|e| e.to_string()))
+}
+
+fn read_extensions_file(path: &Path, default_description: &str) -> HashMap<String, ExtensionEntry> {
+    match read_yaml_value(path) {
+        Ok(Value::Null) => HashMap::new(),
+        Ok(raw) => parse_extensions_value(raw, &path.to_string_lossy(), default_description),
+        Err(err) => {
             warn!(
-                "Expected object for {}, got {}. Using empty map.",
-                EXTENSIONS_CONFIG_KEY, other
+                "Failed to load project extensions from {}: {err}",
+                path.display()
             );
             HashMap::new()
         }
-    };
+    }
+}
+
+// Read once so every accessor agrees on whether platform entries exist.
+static INJECT_PLATFORM_EXTENSIONS: Lazy<bool> = Lazy::new(|| {
+    // Unit tests must not read the user's config.
+    if cfg!(test) {
+        return true;
+    }
+    Config::global()
+        .get_param::<bool>(INJECT_PLATFORM_EXTENSIONS_CONFIG_KEY)
+        .unwrap_or(true)
+});
 
-    if !extensions_map.is_empty() {
+/// Adds an enabled entry for each `PLATFORM_EXTENSIONS` key the map lacks, unless the map is
+/// empty or `inject_platform_extensions` is set to false, in which case reads return exactly
+/// the stored entries. That setting doesn't touch the built-in `DEFAULT_EXTENSION`, which is
+/// never injected; but if `default_extension` names a platform extension, it is only seen
+/// once stored, which `ensure_default_extension_enabled` does.
+fn inject_platform_extensions(extensions_map: &mut HashMap<String, ExtensionEntry>) {
+    if *INJECT_PLATFORM_EXTENSIONS && !extensions_map.is_empty() {
         for (name, def) in PLATFORM_EXTENSIONS.iter() {
             if !extensions_map.contains_key(*name) {
-                extensions_map.insert(
-                    name.to_string(),
-                    ExtensionEntry {
-                        config: ExtensionConfig::Platform {
-                            name: def.name.to_string(),
-                            description: def.description.to_string(),
-                            bundled: Some(true),
-                            available_tools: Vec::new(),
-                        },
-                        enabled: true,
-                    },
-                );
+                extensions_map.insert(name.to_string(), platform_entry(def, true));
             }
         }
     }
-    extensions_map
 }
 
-fn save_extensions_map(extensions: HashMap<String, ExtensionEntry>) {
-    let config = Config::global();
-    match serde_json::to_value(extensions) {
-        Ok(value) => {
-            if let Err(e) = config.set_param(EXTENSIONS_CONFIG_KEY, value) {
-                tracing::debug!("Failed to save extensions config: {}", e);
-            }
-        }
-        Err(e) => {
-            tracing::debug!("Failed to serialize extensions: {}", e);
-        }
+/// The `PLATFORM_EXTENSIONS` keys with no entry in the stored config, sorted. Reads see
+/// these only through injection, which skips an empty config, so they are reported
+/// whether or not the config is currently empty.
+pub fn missing_platform_extensions() -> Vec<String> {
+    missing_platform_keys(&read_global_extensions())
+}
+
+/// Stores an enabled entry for every key `missing_platform_extensions` reports, returning
+/// those keys.
+pub fn restore_missing_platform_extensions() -> Result<Vec<String>, SaveError> {
+    let mut extensions = read_global_extensions();
+    let missing = missing_platform_keys(&extensions);
+    if missing.is_empty() {
+        return Ok(missing);
     }
+    for key in &missing {
+        extensions.insert(
+            key.clone(),
+            platform_entry(&PLATFORM_EXTENSIONS[key.as_str()], true),
+        );
+    }
+    save_extensions_map(extensions)?;
+    Ok(missing)
 }
 
-pub fn get_extension_by_name(name: &str) -> Option<ExtensionConfig> {
-    let extensions = get_extensions_map();
-    extensions
-        .values()
-        .find(|entry| entry.config.name() == name)
-        .map(|entry| entry.config.clone())
+fn missing_platform_keys(extensions: &HashMap<String, ExtensionEntry>) -> Vec<String> {
+    let mut missing: Vec<String> = PLATFORM_EXTENSIONS
+        .keys()
+        .filter(|key| !extensions.contains_key(**key))
+        .map(|key| key.to_string())
+        .collect();
+    missing.sort();
+    missing
 }
 
-pub fn set_extension(entry: ExtensionEntry) {
-    let mut extensions = get_extensions_map();
-    let key = entry.config.key();
-    extensions.insert(key, entry);
-    save_extensions_map(extensions);
+fn platform_entry(def: &PlatformExtensionDef, enabled: bool) -> ExtensionEntry {
+    ExtensionEntry {
+        config: ExtensionConfig::Platform {
+            name: def.name.to_string(),
+            description: def.description.to_string(),
+            bundled: Some(true),
+            available_tools: Vec::new(),
+        },
+        enabled,
+        load_order: None,
+        updated_at: None,
+        disabled_tools: Vec::new(),
+    }
 }
 
-pub fn remove_extension(key: &str) {
-    let mut extensions = get_extensions_map();
-    extensions.remove(key);
-    save_extensions_map(extensions);
+/// Restores every platform key whose stored entry isn't the matching `Platform` extension,
+/// e.g. after a hand edit redefined it as another variant, which would otherwise shadow the
+/// real extension since injection only fills missing keys. Each restored entry keeps its
+/// `enabled` flag. Returns the corrected keys, sorted.
+pub fn reconcile_platform_extensions() -> Result<Vec<String>, SaveError> {
+    let mut extensions = get_extensions_layer(ExtensionLayer::Global);
+    let corrected = reconcile_platform_entries(&mut extensions);
+    if !corrected.is_empty() {
+        warn!(keys = ?corrected, "Restored redefined platform extensions");
+        save_extensions_map(extensions)?;
+    }
+    Ok(corrected)
 }
 
-pub fn set_extension_enabled(key: &str, enabled: bool) {
-    let mut extensions = get_extensions_map();
-    if let Some(entry) = extensions.get_mut(key) {
-        entry.enabled = enabled;
-        save_extensions_map(extensions);
+fn reconcile_platform_entries(extensions: &mut HashMap<String, ExtensionEntry>) -> Vec<String> {
+    let mut corrected = Vec::new();
+    for (key, def) in PLATFORM_EXTENSIONS.iter() {
+        let Some(entry) = extensions.get_mut(*key) else {
+            continue;
+        };
+        let canonical = matches!(
+            &entry.config,
+            ExtensionConfig::Platform { name, .. } if name == def.name
+        );
+        if !canonical {
+            *entry = platform_entry(def, entry.enabled);
+            corrected.push(key.to_string());
+        }
     }
+    corrected.sort();
+    corrected
 }
 
-pub fn get_all_extensions() -> Vec<ExtensionEntry> {
-    let extensions = get_extensions_map();
-    extensions.into_values().collect()
+/// How `merge_extension_maps` resolves a key present in both maps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeMode {
+    /// The overlay entry replaces the base entry wholesale.
+    ReplaceEntry,
+    /// The overlay config is used, but the base entry's `enabled` flag is kept.
+    PreserveEnabled,
 }
 
-pub fn get_all_extension_names() -> Vec<String> {
-    let extensions = get_extensions_map();
-    extensions.keys().cloned().collect()
+/// Combines two extension maps without touching the stored config. Keys only in one map are
+/// kept as they are; keys in both are resolved according to `mode`.
+pub fn merge_extension_maps(
+    base: HashMap<String, ExtensionEntry>,
+    overlay: HashMap<String, ExtensionEntry>,
+    mode: MergeMode,
+) -> HashMap<String, ExtensionEntry> {
+    let mut merged = base;
+    for (key, mut entry) in overlay {
+        if mode == MergeMode::PreserveEnabled {
+            if let Some(existing) = merged.get(&key) {
+                entry.enabled = existing.enabled;
+            }
+        }
+        merged.insert(key, entry);
+    }
+    merged
 }
 
-pub fn is_extension_enabled(key: &str) -> bool {
-    let extensions = get_extensions_map();
-    extensions.get(key).map(|e| e.enabled).unwrap_or(false)
+/// The effective extensions: the global config with the project overlay (if any) and the
+/// runtime overrides on top.
+#[tracing::instrument(
+    name = "extensions.load",
+    level = "debug",
+    skip_all,
+    fields(entries, malformed_skipped, elapsed_ms)
+)]
+fn get_extensions_map() -> HashMap<String, ExtensionEntry> {
+    let start = Instant::now();
+    take_malformed_skipped();
+    let extensions_map = layer_extensions(
+        read_global_extensions(),
+        read_project_extensions(),
+        &RUNTIME_OVERRIDES.read().unwrap(),
+    );
+    LAST_SEEN_KEYS.lock().unwrap().extensions = Some(extensions_map.keys().cloned().collect());
+    tracing::Span::current().record("malformed_skipped", take_malformed_skipped());
+    record_io_span(start, extensions_map.len());
+    extensions_map
 }
 
-pub fn get_enabled_extensions() -> Vec<ExtensionConfig> {
-    get_all_extensions()
-        .into_iter()
-        .filter(|ext| ext.enabled)
-        .map(|ext| ext.config)
-        .collect()
+/// Stacks the parsed layers into the effective extensions: the project overlay replaces
+/// global entries, then the platform extensions are injected and the runtime overrides
+/// applied. `get_extensions_map` and `try_get_extensions_map` both go through here.
+fn layer_extensions(
+    global: HashMap<String, ExtensionEntry>,
+    project: HashMap<String, ExtensionEntry>,
+    overrides: &HashMap<String, RuntimeOverride>,
+) -> HashMap<String, ExtensionEntry> {
+    let mut extensions = merge_extension_maps(global, project, MergeMode::ReplaceEntry);
+    inject_platform_extensions(&mut extensions);
+    apply_runtime_overrides(&mut extensions, overrides);
+    extensions
+}
+
+/// Like `get_extensions_map`, but keeps only one entry per `config.name()`, for consolidating
+/// a config where several keys ended up with the same name. Nothing is written; each dropped
+/// entry is logged along with the key that was kept.
+pub fn get_extensions_map_deduped_by_name() -> HashMap<String, ExtensionEntry> {
+    let (extensions, dropped) = dedupe_by_name(get_extensions_map());
+    for (dropped_key, kept_key) in dropped {
+        warn!(
+            dropped = %dropped_key,
+            kept = %kept_key,
+            "Dropping extension that shares its name with another"
+        );
+    }
+    extensions
+}
+
+/// Picks one entry per name: an enabled one over a disabled one, then the most recently
+/// updated, then the smallest key. Returns the survivors and `(dropped, kept)` key pairs.
+fn dedupe_by_name(
+    extensions: HashMap<String, ExtensionEntry>,
+) -> (HashMap<String, ExtensionEntry>, Vec<(String, String)>) {
+    let mut by_name: HashMap<String, Vec<(String, ExtensionEntry)>> = HashMap::new();
+    for (key, entry) in extensions {
+        by_name
+            .entry(entry.config.name())
+            .or_default()
+            .push((key, entry));
+    }
+
+    let mut kept = HashMap::new();
+    let mut dropped = Vec::new();
+    for (_, mut entries) in by_name {
+        entries.sort_by(|(a_key, a), (b_key, b)| {
+            b.enabled
+                .cmp(&a.enabled)
+                .then_with(|| b.updated_at.cmp(&a.updated_at))
+                .then_with(|| a_key.cmp(b_key))
+        });
+        let mut entries = entries.into_iter();
+        let (kept_key, entry) = entries.next().expect("grouped names are never empty");
+        dropped.extend(entries.map(|(key, _)| (key, kept_key.clone())));
+        kept.insert(kept_key, entry);
+    }
+    dropped.sort();
+    (kept, dropped)
+}
+
+/// A single layer, as mutators must read it so that a save doesn't leak entries from one
+/// layer into the other.
+fn get_extensions_layer(layer: ExtensionLayer) -> HashMap<String, ExtensionEntry> {
+    match layer {
+        ExtensionLayer::Global => {
+            let mut extensions_map = read_global_extensions();
+            inject_platform_extensions(&mut extensions_map);
+            extensions_map
+        }
+        ExtensionLayer::Project => read_project_extensions(),
+    }
+}
+
+fn save_extensions_map(extensions: HashMap<String, ExtensionEntry>) -> Result<(), SaveError> {
+    save_extensions_layer(ExtensionLayer::Global, extensions)
+}
+
+#[tracing::instrument(
+    name = "extensions.save",
+    level = "debug",
+    skip_all,
+    fields(layer = ?layer, entries, elapsed_ms)
+)]
+fn save_extensions_layer(
+    layer: ExtensionLayer,
+    extensions: HashMap<String, ExtensionEntry>,
+) -> Result<(), SaveError> {
+    let start = Instant::now();
+    let entries = extensions.len();
+    let result = write_extensions_layer(layer, extensions);
+    invalidate_extension_index();
+    record_io_span(start, entries);
+    result
+}
+
+fn write_extensions_layer(
+    layer: ExtensionLayer,
+    extensions: HashMap<String, ExtensionEntry>,
+) -> Result<(), SaveError> {
+    match layer {
+        ExtensionLayer::Global => {
+            let value = serde_json::to_value(extensions).map_err(|e| {
+                tracing::debug!("Failed to serialize extensions: {}", e);
+                SaveError::Serialize {
+                    key: EXTENSIONS_CONFIG_KEY.to_string(),
+                    error: e.to_string(),
+                }
+            })?;
+            let previous = read_raw_config_state();
+            Config::global()
+                .set_param(EXTENSIONS_CONFIG_KEY, value)
+                .map_err(|e| {
+                    tracing::debug!("Failed to save extensions config: {}", e);
+                    SaveError::Write {
+                        key: EXTENSIONS_CONFIG_KEY.to_string(),
+                        error: e.to_string(),
+                    }
+                })?;
+            push_undo_state(previous);
+            Ok(())
+        }
+        ExtensionLayer::Project => {
+            let Some(path) = project_overlay_path() else {
+                tracing::debug!("No project overlay configured; not saving project extensions");
+                return Err(SaveError::NoProjectOverlay);
+            };
+            let content = serde_yaml::to_string(&extensions).map_err(|e| {
+                tracing::debug!("Failed to serialize project extensions: {}", e);
+                SaveError::Serialize {
+                    key: path.display().to_string(),
+                    error: e.to_string(),
+                }
+            })?;
+            let result = path
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|_| std::fs::write(&path, content));
+            result.map_err(|e| {
+                tracing::debug!(
+                    "Failed to save project extensions to {}: {}",
+                    path.display(),
+                    e
+                );
+                SaveError::Write {
+                    key: path.display().to_string(),
+                    error: e.to_string(),
+                }
+            })
+        }
+    }
+}
+
+/// Constant-time name and tool lookups over the effective extensions, for hosts that query
+/// the config many times per agent turn. `current` builds it on first use and shares it until
+/// the next save through this module, or until `reload_extensions` picks up outside edits.
+#[derive(Debug, Default)]
+pub struct ExtensionIndex {
+    extensions: HashMap<String, ExtensionEntry>,
+    keys_by_name: HashMap<String, String>,
+    keys_by_tool: HashMap<String, Vec<String>>,
+}
+
+#[derive(Default)]
+struct IndexCache {
+    // Bumped on every invalidation so an index built from a map that was saved over while
+    // it was being built is never stored.
+    generation: u64,
+    index: Option<Arc<ExtensionIndex>>,
+}
+
+static EXTENSION_INDEX: Lazy<Mutex<IndexCache>> = Lazy::new(|| Mutex::new(IndexCache::default()));
+
+impl ExtensionIndex {
+    /// A shared handle to the index of the current config, building it if needed.
+    pub fn current() -> Arc<ExtensionIndex> {
+        let generation = {
+            let cache = EXTENSION_INDEX.lock().unwrap();
+            if let Some(index) = &cache.index {
+                return Arc::clone(index);
+            }
+            cache.generation
+        };
+
+        let index = Arc::new(ExtensionIndex::from_extensions(get_extensions_map()));
+        let mut cache = EXTENSION_INDEX.lock().unwrap();
+        if cache.generation == generation {
+            cache.index = Some(Arc::clone(&index));
+        }
+        index
+    }
+
+    /// Indexes `extensions`. On a name shared by several extensions the smallest key wins,
+    /// as in `get_extension_by_name`.
+    pub fn from_extensions(extensions: HashMap<String, ExtensionEntry>) -> Self {
+        let mut keys_by_name: HashMap<String, String> = HashMap::with_capacity(extensions.len());
+        let mut keys_by_tool: HashMap<String, Vec<String>> = HashMap::new();
+        for (key, entry) in &extensions {
+            keys_by_name
+                .entry(entry.config.name())
+                .and_modify(|existing| {
+                    if key < existing {
+                        *existing = key.clone();
+                    }
+                })
+                .or_insert_with(|| key.clone());
+
+            let mut tools: Vec<String> = entry.config.available_tools().to_vec();
+            if let ExtensionConfig::Frontend {
+                tools: frontend_tools,
+                ..
+            } = &entry.config
+            {
+                tools.extend(frontend_tools.iter().map(|tool| tool.name.to_string()));
+            }
+            for tool in tools {
+                let keys = keys_by_tool.entry(tool).or_default();
+                if !keys.contains(key) {
+                    keys.push(key.clone());
+                }
+            }
+        }
+        for keys in keys_by_tool.values_mut() {
+            keys.sort();
+        }
+
+        Self {
+            extensions,
+            keys_by_name,
+            keys_by_tool,
+        }
+    }
+
+    pub fn key_for_name(&self, name: &str) -> Option<&str> {
+        self.keys_by_name.get(name).map(String::as_str)
+    }
+
+    pub fn get_by_name(&self, name: &str) -> Option<&ExtensionEntry> {
+        self.key_for_name(name)
+            .and_then(|key| self.extensions.get(key))
+    }
+
+    pub fn get(&self, key: &str) -> Option<&ExtensionEntry> {
+        self.extensions.get(key)
+    }
+
+    /// Keys of the extensions that list `tool` among their available tools, sorted.
+    pub fn keys_for_tool(&self, tool: &str) -> &[String] {
+        self.keys_by_tool
+            .get(tool)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.extensions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.extensions.is_empty()
+    }
+}
+
+/// Drops the index along with the cached group states, which depend on the same maps.
+fn invalidate_extension_index() {
+    invalidate_caches(&GROUP_STATE_CACHE);
+}
+
+/// Drops the cached index along with the group states held in `group_states`.
+fn invalidate_caches(group_states: &Mutex<GroupStateCache>) {
+    let mut cache = EXTENSION_INDEX.lock().unwrap();
+    cache.generation += 1;
+    cache.index = None;
+    drop(cache);
+    group_states.lock().unwrap().invalidate();
+}
+
+const UNDO_HISTORY_LIMIT: usize = 10;
+
+/// The stored extensions and groups values exactly as they were before a save; `None`
+/// means the key was absent.
+#[derive(Debug, Clone, PartialEq)]
+struct RawConfigState {
+    extensions: Option<Value>,
+    groups: Option<Value>,
+}
+
+// Per-process undo history. Every save to the global config pushes the state it is about
+// to overwrite, so all mutators are undoable without each having to opt in.
+static UNDO_HISTORY: Lazy<Mutex<VecDeque<RawConfigState>>> =
+    Lazy::new(|| Mutex::new(VecDeque::with_capacity(UNDO_HISTORY_LIMIT)));
+
+fn read_raw_config_state() -> RawConfigState {
+    let config = Config::global();
+    RawConfigState {
+        extensions: config.get_param::<Value>(EXTENSIONS_CONFIG_KEY).ok(),
+        groups: config.get_param::<Value>(EXTENSION_GROUPS_CONFIG_KEY).ok(),
+    }
+}
+
+/// Writes both keys in a single config write, so either both land or neither does.
+fn write_raw_config_state(state: RawConfigState) -> Result<(), SaveError> {
+    let result = Config::global()
+        .set_params([
+            (EXTENSIONS_CONFIG_KEY, state.extensions),
+            (EXTENSION_GROUPS_CONFIG_KEY, state.groups),
+        ])
+        .map_err(|e| {
+            tracing::debug!("Failed to restore extension config: {}", e);
+            SaveError::Write {
+                key: format!("{EXTENSIONS_CONFIG_KEY} and {EXTENSION_GROUPS_CONFIG_KEY}"),
+                error: e.to_string(),
+            }
+        });
+    invalidate_extension_index();
+    result
+}
+
+/// Saves both maps in one write and one undo entry, for changes that touch extensions and
+/// groups together and mustn't land only halfway.
+fn save_extensions_and_groups(
+    extensions: HashMap<String, ExtensionEntry>,
+    groups: HashMap<String, ExtensionGroup>,
+) -> Result<(), SaveError> {
+    let serialize = |key: &str, error: serde_json::Error| {
+        tracing::debug!("Failed to serialize {}: {}", key, error);
+        SaveError::Serialize {
+            key: key.to_string(),
+            error: error.to_string(),
+        }
+    };
+    let state = RawConfigState {
+        extensions: Some(
+            serde_json::to_value(extensions).map_err(|e| serialize(EXTENSIONS_CONFIG_KEY, e))?,
+        ),
+        groups: Some(
+            serde_json::to_value(groups).map_err(|e| serialize(EXTENSION_GROUPS_CONFIG_KEY, e))?,
+        ),
+    };
+    let previous = read_raw_config_state();
+    write_raw_config_state(state)?;
+    push_undo_state(previous);
+    Ok(())
+}
+
+fn push_undo_state(state: RawConfigState) {
+    let mut history = UNDO_HISTORY.lock().unwrap();
+    push_bounded(&mut *history, state, UNDO_HISTORY_LIMIT);
+}
+
+fn push_bounded<T>(history: &mut VecDeque<T>, item: T, limit: usize) {
+    while history.len() >= limit {
+        history.pop_front();
+    }
+    history.push_back(item);
+}
+
+/// Restores the extensions and groups to how they were before the most recent save in
+/// this process. Only the last `UNDO_HISTORY_LIMIT` changes are kept; if the write fails the
+/// change stays in the history so the undo can be retried.
+pub fn undo_last_extension_change() -> Result<(), ExtensionError> {
+    let state = UNDO_HISTORY
+        .lock()
+        .unwrap()
+        .pop_back()
+        .ok_or(ExtensionError::NothingToUndo)?;
+    if let Err(e) = write_raw_config_state(state.clone()) {
+        UNDO_HISTORY.lock().unwrap().push_back(state);
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+/// A point-in-time copy of the stored extensions and groups, serializable so callers can
+/// keep it on disk across processes. `None` means the key was absent when the snapshot was
+/// taken and will be removed again on restore.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfigSnapshot {
+    /// Unix seconds at which the snapshot was taken.
+    pub taken_at: i64,
+    #[serde(default)]
+    pub extensions: Option<Value>,
+    #[serde(default)]
+    pub groups: Option<Value>,
+}
+
+/// Captures the stored extensions and groups exactly as they are, for a later
+/// `restore_config`.
+pub fn snapshot_config() -> ConfigSnapshot {
+    let state = read_raw_config_state();
+    ConfigSnapshot {
+        taken_at: now_unix(),
+        extensions: state.extensions,
+        groups: state.groups,
+    }
+}
+
+/// Writes a snapshot back over the stored extensions and groups in a single write; if it
+/// fails neither map is changed. A successful restore can itself be undone with
+/// `undo_last_extension_change`.
+pub fn restore_config(snapshot: &ConfigSnapshot) -> Result<(), SaveError> {
+    let previous = read_raw_config_state();
+    write_raw_config_state(RawConfigState {
+        extensions: snapshot.extensions.clone(),
+        groups: snapshot.groups.clone(),
+    })?;
+    push_undo_state(previous);
+    Ok(())
+}
+
+/// The keys this process last read, so `reload_extensions` can report what changed.
+#[derive(Default)]
+struct LastSeenKeys {
+    extensions: Option<HashSet<String>>,
+    groups: Option<HashSet<String>>,
+}
+
+static LAST_SEEN_KEYS: Lazy<Mutex<LastSeenKeys>> =
+    Lazy::new(|| Mutex::new(LastSeenKeys::default()));
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, ToSchema)]
+pub struct ExtensionReloadSummary {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub added_groups: Vec<String>,
+    pub removed_groups: Vec<String>,
+}
+
+impl ExtensionReloadSummary {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.added_groups.is_empty()
+            && self.removed_groups.is_empty()
+    }
+}
+
+/// Re-reads the extensions and groups from `Config::global()` (re-running platform
+/// injection) and reports which keys appeared or disappeared since this process last read
+/// them, e.g. after another tool edited the config file.
+pub fn reload_extensions() -> ExtensionReloadSummary {
+    let previous = std::mem::take(&mut *LAST_SEEN_KEYS.lock().unwrap());
+    invalidate_extension_index();
+    let extensions: HashSet<String> = get_extensions_map().into_keys().collect();
+    let groups: HashSet<String> = get_extension_groups_map().into_keys().collect();
+
+    let (added, removed) = diff_keys(&previous.extensions.unwrap_or_default(), &extensions);
+    let (added_groups, removed_groups) = diff_keys(&previous.groups.unwrap_or_default(), &groups);
+    ExtensionReloadSummary {
+        added,
+        removed,
+        added_groups,
+        removed_groups,
+    }
+}
+
+fn diff_keys(before: &HashSet<String>, after: &HashSet<String>) -> (Vec<String>, Vec<String>) {
+    let mut added: Vec<String> = after.difference(before).cloned().collect();
+    let mut removed: Vec<String> = before.difference(after).cloned().collect();
+    added.sort();
+    removed.sort();
+    (added, removed)
+}
+
+/// How often the watcher checks the config file's modification time.
+const WATCH_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// A change to the stored config noticed by `watch_extensions_config`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ExtensionChangeEvent {
+    Added { key: String },
+    Removed { key: String },
+    Toggled { key: String, enabled: bool },
+    GroupAdded { key: String },
+    GroupRemoved { key: String },
+}
+
+/// Keeps a `watch_extensions_config` watcher running; dropping it stops the watcher.
+pub struct WatchHandle {
+    stop: Option<std::sync::mpsc::Sender<()>>,
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+impl Drop for WatchHandle {
+    fn drop(&mut self) {
+        // Disconnecting the channel wakes the watcher immediately.
+        self.stop.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// The part of the stored config that change events describe.
+#[derive(Debug, Default, PartialEq)]
+struct WatchedState {
+    extensions: HashMap<String, bool>,
+    groups: HashSet<String>,
+}
+
+impl WatchedState {
+    fn read() -> Self {
+        WatchedState {
+            extensions: get_extensions_layer(ExtensionLayer::Global)
+                .into_iter()
+                .map(|(key, entry)| (key, entry.enabled))
+                .collect(),
+            groups: read_extension_groups().into_keys().collect(),
+        }
+    }
+
+    /// Events turning `self` into `next`: extension events first, each kind sorted by key.
+    fn diff(&self, next: &WatchedState) -> Vec<ExtensionChangeEvent> {
+        let before: HashSet<String> = self.extensions.keys().cloned().collect();
+        let after: HashSet<String> = next.extensions.keys().cloned().collect();
+        let (added, removed) = diff_keys(&before, &after);
+
+        let mut toggled: Vec<(&String, bool)> = next
+            .extensions
+            .iter()
+            .filter(|(key, enabled)| self.extensions.get(*key).is_some_and(|was| was != *enabled))
+            .map(|(key, enabled)| (key, *enabled))
+            .collect();
+        toggled.sort();
+
+        let (added_groups, removed_groups) = diff_keys(&self.groups, &next.groups);
+
+        let mut events: Vec<ExtensionChangeEvent> = Vec::new();
+        events.extend(
+            added
+                .into_iter()
+                .map(|key| ExtensionChangeEvent::Added { key }),
+        );
+        events.extend(
+            removed
+                .into_iter()
+                .map(|key| ExtensionChangeEvent::Removed { key }),
+        );
+        events.extend(
+            toggled
+                .into_iter()
+                .map(|(key, enabled)| ExtensionChangeEvent::Toggled {
+                    key: key.clone(),
+                    enabled,
+                }),
+        );
+        events.extend(
+            added_groups
+                .into_iter()
+                .map(|key| ExtensionChangeEvent::GroupAdded { key }),
+        );
+        events.extend(
+            removed_groups
+                .into_iter()
+                .map(|key| ExtensionChangeEvent::GroupRemoved { key }),
+        );
+        events
+    }
+}
+
+/// Watches the global config file from a background thread and calls `callback` for every
+/// extension or group that was added, removed or toggled since the last look, including
+/// edits made by other processes such as the CLI. The file's modification time, and that of
+/// the project overlay in effect when the watch starts, is checked every
+/// `WATCH_POLL_INTERVAL`. Any change, even one that yields no events such as an edited
+/// description, drops the cached index and group states so later reads see the new file.
+pub fn watch_extensions_config(
+    callback: impl Fn(ExtensionChangeEvent) + Send + 'static,
+) -> WatchHandle {
+    let mut paths = vec![PathBuf::from(Config::global().path())];
+    paths.extend(project_overlay_path());
+    watch_config(
+        paths,
+        WATCH_POLL_INTERVAL,
+        WatchedState::read,
+        &GROUP_STATE_CACHE,
+        callback,
+    )
+}
+
+/// `watch_extensions_config` with the file locations, the way the state is read and the group
+/// state cache to drop passed in.
+fn watch_config(
+    paths: Vec<PathBuf>,
+    interval: std::time::Duration,
+    read_state: impl Fn() -> WatchedState + Send + 'static,
+    group_states: &'static Mutex<GroupStateCache>,
+    callback: impl Fn(ExtensionChangeEvent) + Send + 'static,
+) -> WatchHandle {
+    let mut last_state = read_state();
+    watch_paths(paths, interval, move || {
+        invalidate_caches(group_states);
+        let state = read_state();
+        let events = last_state.diff(&state);
+        last_state = state;
+        for event in events {
+            callback(event);
+        }
+    })
+}
+
+/// Calls `on_change` from a background thread whenever the modification time of any of
+/// `paths` changes, checking every `interval`. A missing file has no modification time, so
+/// creating or deleting one counts as a change.
+fn watch_paths(
+    paths: Vec<PathBuf>,
+    interval: std::time::Duration,
+    mut on_change: impl FnMut() + Send + 'static,
+) -> WatchHandle {
+    let (stop, stopped) = std::sync::mpsc::channel::<()>();
+    let modified = move || -> Vec<Option<std::time::SystemTime>> {
+        paths
+            .iter()
+            .map(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok())
+            .collect()
+    };
+
+    let thread = std::thread::Builder::new()
+        .name("extensions-config-watch".to_string())
+        .spawn(move || {
+            let mut last_modified = modified();
+            while let Err(std::sync::mpsc::RecvTimeoutError::Timeout) =
+                stopped.recv_timeout(interval)
+            {
+                let current_modified = modified();
+                if current_modified != last_modified {
+                    last_modified = current_modified;
+                    on_change();
+                }
+            }
+        });
+
+    match thread {
+        Ok(thread) => WatchHandle {
+            stop: Some(stop),
+            thread: Some(thread),
+        },
+        Err(e) => {
+            warn!("Failed to start extension config watcher: {}", e);
+            WatchHandle {
+                stop: None,
+                thread: None,
+            }
+        }
+    }
+}
+
+/// One fix made (or, in a dry run, proposed) by `normalize_config`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum NormalizationChange {
+    /// The extension was stored under a key other than the one its name derives.
+    Rekeyed {
+        from: String,
+        to: String,
+    },
+    /// Re-keying was skipped because another extension already holds the derived key.
+    RekeyConflict {
+        from: String,
+        to: String,
+    },
+    DescriptionFilled {
+        key: String,
+    },
+    DuplicateMembersRemoved {
+        group: String,
+        members: Vec<String>,
+    },
+    /// A group member that was re-keyed, or written in non-canonical form, now points at the
+    /// canonical key.
+    MemberRenamed {
+        group: String,
+        from: String,
+        to: String,
+    },
+    OrphanedMemberPruned {
+        group: String,
+        member: String,
+    },
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, ToSchema)]
+pub struct NormalizationReport {
+    pub changes: Vec<NormalizationChange>,
+    /// Whether the changes were only previewed rather than written.
+    pub dry_run: bool,
+}
+
+impl NormalizationReport {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+/// Cleans up the stored config in one pass: re-keys extensions through `name_to_key`, fills
+/// missing descriptions, removes duplicate group members and prunes group members that no
+/// longer refer to any extension. With `dry_run` the report is computed but nothing is
+/// written; otherwise both maps are written together as a single undoable change.
+pub fn normalize_config(dry_run: bool) -> Result<NormalizationReport, SaveError> {
+    let raw = read_raw_config_state();
+    let extensions = match &raw.extensions {
+        Some(Value::Object(obj)) => obj.clone(),
+        _ => serde_json::Map::new(),
+    };
+    let mut known_keys: HashSet<String> = PLATFORM_EXTENSIONS
+        .keys()
+        .map(|key| key.to_string())
+        .collect();
+    known_keys.extend(read_project_extensions().into_keys());
+
+    let normalized = normalize_maps(
+        extensions,
+        get_extension_groups_map(),
+        &known_keys,
+        &get_default_extension_description(),
+    );
+    let report = NormalizationReport {
+        changes: normalized.changes,
+        dry_run,
+    };
+    if dry_run || !(normalized.extensions_changed || normalized.groups_changed) {
+        return Ok(report);
+    }
+
+    let mut state = raw.clone();
+    if normalized.extensions_changed {
+        state.extensions = Some(Value::Object(normalized.extensions));
+    }
+    if normalized.groups_changed {
+        let groups = serde_json::to_value(normalized.groups).map_err(|e| {
+            tracing::debug!("Failed to serialize extension groups: {}", e);
+            SaveError::Serialize {
+                key: EXTENSION_GROUPS_CONFIG_KEY.to_string(),
+                error: e.to_string(),
+            }
+        })?;
+        state.groups = Some(groups);
+    }
+    write_raw_config_state(state)?;
+    push_undo_state(raw);
+    Ok(report)
+}
+
+struct NormalizedMaps {
+    extensions: serde_json::Map<String, Value>,
+    groups: HashMap<String, ExtensionGroup>,
+    changes: Vec<NormalizationChange>,
+    extensions_changed: bool,
+    groups_changed: bool,
+}
+
+/// `known_keys` are keys that count as existing even though they aren't stored in the
+/// global map, i.e. platform extensions and the project overlay.
+fn normalize_maps(
+    extensions: serde_json::Map<String, Value>,
+    mut groups: HashMap<String, ExtensionGroup>,
+    known_keys: &HashSet<String>,
+    default_description: &str,
+) -> NormalizedMaps {
+    let mut changes = Vec::new();
+
+    let mut keys: Vec<String> = extensions.keys().cloned().collect();
+    keys.sort();
+    let mut extensions = extensions;
+    let mut renamed: HashMap<String, String> = HashMap::new();
+    let mut extensions_changed = false;
+    for key in keys {
+        let Some(mut value) = extensions.remove(&key) else {
+            continue;
+        };
+        if matches!(value.get("description"), None | Some(Value::Null)) && value.is_object() {
+            fill_missing_description(&mut value, default_description);
+            changes.push(NormalizationChange::DescriptionFilled { key: key.clone() });
+            extensions_changed = true;
+        }
+        let derived = serde_json::from_value::<ExtensionEntry>(value.clone())
+            .map(|entry| entry.config.key())
+            .unwrap_or_else(|_| key.clone());
+        if derived == key {
+            extensions.insert(key, value);
+        } else if extensions.contains_key(&derived) {
+            changes.push(NormalizationChange::RekeyConflict {
+                from: key.clone(),
+                to: derived,
+            });
+            extensions.insert(key, value);
+        } else {
+            changes.push(NormalizationChange::Rekeyed {
+                from: key.clone(),
+                to: derived.clone(),
+            });
+            renamed.insert(key, derived.clone());
+            extensions.insert(derived, value);
+            extensions_changed = true;
+        }
+    }
+
+    let mut group_keys: Vec<String> = groups.keys().cloned().collect();
+    group_keys.sort();
+    let mut groups_changed = false;
+    for group_key in group_keys {
+        let Some(group) = groups.get_mut(&group_key) else {
+            continue;
+        };
+        let original = group.clone();
+        for member in &mut group.extension_keys {
+            if let Some(to) = renamed.get(member.as_str()) {
+                changes.push(NormalizationChange::MemberRenamed {
+                    group: group_key.clone(),
+                    from: std::mem::replace(member, to.clone()),
+                    to: to.clone(),
+                });
+            }
+        }
+        let mut seen = HashSet::new();
+        let mut duplicates = Vec::new();
+        for member in &group.extension_keys {
+            let canonical = name_to_key(member);
+            if canonical != *member {
+                changes.push(NormalizationChange::MemberRenamed {
+                    group: group_key.clone(),
+                    from: member.clone(),
+                    to: canonical.clone(),
+                });
+            }
+            if !seen.insert(canonical.clone()) && !duplicates.contains(&canonical) {
+                duplicates.push(canonical);
+            }
+        }
+        normalize_group_members(group);
+        let (members, orphaned): (Vec<String>, Vec<String>) =
+            std::mem::take(&mut group.extension_keys)
+                .into_iter()
+                .partition(|member| extensions.contains_key(member) || known_keys.contains(member));
+        changes.extend(orphaned.into_iter().map(|member| {
+            NormalizationChange::OrphanedMemberPruned {
+                group: group_key.clone(),
+                member,
+            }
+        }));
+        if !duplicates.is_empty() {
+            changes.push(NormalizationChange::DuplicateMembersRemoved {
+                group: group_key.clone(),
+                members: duplicates,
+            });
+        }
+        group.extension_keys = members;
+        groups_changed |= *group != original;
+    }
+
+    NormalizedMaps {
+        extensions,
+        groups,
+        changes,
+        extensions_changed,
+        groups_changed,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProblemSeverity {
+    /// The entry can't be loaded or used as configured.
+    Error,
+    /// Works, but is ambiguous or will drift further.
+    Warning,
+    Info,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ConfigProblemKind {
+    /// The stored value doesn't parse, so the loaders skip it.
+    Malformed {
+        error: String,
+    },
+    /// Fails `validate_extension_config`, e.g. an out-of-range timeout.
+    InvalidConfig {
+        error: String,
+    },
+    /// Stored under a key other than the one its name derives.
+    UnnormalizedKey {
+        expected: String,
+    },
+    /// Shares its display name with the other `keys`.
+    DuplicateName {
+        name: String,
+        keys: Vec<String>,
+    },
+    /// The group's key is also used by an extension.
+    KeyCollision,
+    /// An explicit group member that isn't any known extension.
+    OrphanedMember {
+        member: String,
+    },
+    MissingPlatformExtension,
+}
+
+/// A call that resolves the problem, for UIs offering one-click remediation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum SuggestedFix {
+    /// `normalize_config(false)`.
+    NormalizeConfig,
+    /// `remove_extension(key)`.
+    RemoveExtension { key: String },
+    /// `remove_extension_from_group(group, member)`.
+    RemoveGroupMember { group: String, member: String },
+    /// `restore_missing_platform_extensions()`.
+    RestorePlatformExtensions,
+}
+
+/// One finding of `validate_config`. `key` names the extension and `group` the group it
+/// concerns; a problem may name both.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct ConfigProblem {
+    pub severity: ProblemSeverity,
+    pub key: Option<String>,
+    pub group: Option<String>,
+    #[serde(flatten)]
+    pub kind: ConfigProblemKind,
+    pub fix: Option<SuggestedFix>,
+}
+
+impl ConfigProblem {
+    fn extension(severity: ProblemSeverity, key: &str, kind: ConfigProblemKind) -> Self {
+        Self {
+            severity,
+            key: Some(key.to_string()),
+            group: None,
+            kind,
+            fix: None,
+        }
+    }
+
+    fn group(severity: ProblemSeverity, group: &str, kind: ConfigProblemKind) -> Self {
+        Self {
+            severity,
+            key: None,
+            group: Some(group.to_string()),
+            kind,
+            fix: None,
+        }
+    }
+
+    fn with_fix(mut self, fix: SuggestedFix) -> Self {
+        self.fix = Some(fix);
+        self
+    }
+}
+
+/// Runs every config check over a single read of both maps: malformed entries and groups,
+/// invalid extension configs, un-normalized keys, duplicate names, group keys that collide
+/// with extension keys, orphaned group members and missing platform extensions. Problems
+/// are ordered by severity, then by key and group. Nothing is written.
+pub fn validate_config() -> Vec<ConfigProblem> {
+    let raw = read_raw_config_state();
+    let mut problems = Vec::new();
+
+    let extensions = match raw.extensions {
+        None => HashMap::new(),
+        Some(Value::Object(obj)) => {
+            let (extensions, malformed) =
+                parse_extension_entries(obj, &get_default_extension_description());
+            problems.extend(malformed.into_iter().map(|m| {
+                ConfigProblem::extension(
+                    ProblemSeverity::Error,
+                    &m.key,
+                    ConfigProblemKind::Malformed { error: m.error },
+                )
+                .with_fix(SuggestedFix::RemoveExtension { key: m.key.clone() })
+            }));
+            extensions
+        }
+        Some(other) => {
+            problems.push(ConfigProblem {
+                severity: ProblemSeverity::Error,
+                key: None,
+                group: None,
+                kind: ConfigProblemKind::Malformed {
+                    error: format!("expected an object of extensions, got {other}"),
+                },
+                fix: None,
+            });
+            HashMap::new()
+        }
+    };
+
+    let mut groups = HashMap::new();
+    match raw.groups {
+        None => {}
+        Some(Value::Object(obj)) => {
+            for (key, value) in obj {
+                match serde_json::from_value::<ExtensionGroup>(value) {
+                    Ok(group) => {
+                        groups.insert(key, group);
+                    }
+                    Err(e) => problems.push(ConfigProblem::group(
+                        ProblemSeverity::Error,
+                        &key,
+                        ConfigProblemKind::Malformed {
+                            error: e.to_string(),
+                        },
+                    )),
+                }
+            }
+        }
+        Some(other) => problems.push(ConfigProblem {
+            severity: ProblemSeverity::Error,
+            key: None,
+            group: None,
+            kind: ConfigProblemKind::Malformed {
+                error: format!("expected an object of extension groups, got {other}"),
+            },
+            fix: None,
+        }),
+    }
+
+    let overlay_keys: HashSet<String> = read_project_extensions().into_keys().collect();
+    problems.extend(config_problems(
+        &extensions,
+        &groups,
+        &overlay_keys,
+        *INJECT_PLATFORM_EXTENSIONS,
+    ));
+    problems.sort_by(|a, b| (a.severity, &a.key, &a.group).cmp(&(b.severity, &b.key, &b.group)));
+    problems
+}
+
+/// The checks of `validate_config` that work on parsed maps. `overlay_keys` are the project
+/// overlay's keys, which count as existing for group members and key collisions.
+fn config_problems(
+    extensions: &HashMap<String, ExtensionEntry>,
+    groups: &HashMap<String, ExtensionGroup>,
+    overlay_keys: &HashSet<String>,
+    inject_platform: bool,
+) -> Vec<ConfigProblem> {
+    let mut problems = Vec::new();
+
+    for (key, entry) in extensions {
+        if let Err(e) = validate_extension_config(&entry.config) {
+            problems.push(ConfigProblem::extension(
+                ProblemSeverity::Error,
+                key,
+                ConfigProblemKind::InvalidConfig {
+                    error: e.to_string(),
+                },
+            ));
+        }
+        let expected = entry.config.key();
+        if expected != *key {
+            problems.push(
+                ConfigProblem::extension(
+                    ProblemSeverity::Warning,
+                    key,
+                    ConfigProblemKind::UnnormalizedKey { expected },
+                )
+                .with_fix(SuggestedFix::NormalizeConfig),
+            );
+        }
+    }
+
+    for (name, keys) in duplicate_names(extensions) {
+        for key in &keys {
+            problems.push(ConfigProblem::extension(
+                ProblemSeverity::Warning,
+                key,
+                ConfigProblemKind::DuplicateName {
+                    name: name.clone(),
+                    keys: keys.clone(),
+                },
+            ));
+        }
+    }
+
+    // With injection off, leaving platform extensions out is deliberate.
+    let missing_platform = if inject_platform {
+        missing_platform_keys(extensions)
+    } else {
+        Vec::new()
+    };
+    let is_known = |key: &str| {
+        extensions.contains_key(key)
+            || overlay_keys.contains(key)
+            || (inject_platform && PLATFORM_EXTENSIONS.contains_key(key))
+    };
+    for (group_key, group) in groups {
+        if is_known(group_key) {
+            problems.push(ConfigProblem::group(
+                ProblemSeverity::Error,
+                group_key,
+                ConfigProblemKind::KeyCollision,
+            ));
+        }
+        for member in &group.extension_keys {
+            if !is_known(member) {
+                problems.push(
+                    ConfigProblem::group(
+                        ProblemSeverity::Warning,
+                        group_key,
+                        ConfigProblemKind::OrphanedMember {
+                            member: member.clone(),
+                        },
+                    )
+                    .with_fix(SuggestedFix::RemoveGroupMember {
+                        group: group_key.clone(),
+                        member: member.clone(),
+                    }),
+                );
+            }
+        }
+    }
+
+    problems.extend(missing_platform.into_iter().map(|key| {
+        ConfigProblem::extension(
+            ProblemSeverity::Info,
+            &key,
+            ConfigProblemKind::MissingPlatformExtension,
+        )
+        .with_fix(SuggestedFix::RestorePlatformExtensions)
+    }));
+    problems
+}
+
+/// Checks the parts of an extension config that serde can't: timeouts must be within
+/// `1..=MAX_EXTENSION_TIMEOUT`, remote URIs must parse and custom headers must have
+/// non-empty names and values.
+pub fn validate_extension_config(config: &ExtensionConfig) -> Result<(), ExtensionValidationError> {
+    if let Some(timeout) = extension_timeout(config) {
+        if timeout == 0 || timeout > MAX_EXTENSION_TIMEOUT {
+            return Err(ExtensionValidationError::InvalidTimeout(timeout));
+        }
+    }
+
+    match config {
+        ExtensionConfig::Sse { uri, .. } => validate_uri(uri),
+        ExtensionConfig::StreamableHttp { uri, headers, .. } => {
+            validate_uri(uri)?;
+            for (name, value) in headers {
+                if name.trim().is_empty() || value.trim().is_empty() {
+                    return Err(ExtensionValidationError::EmptyHeader(name.clone()));
+                }
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+fn extension_timeout(config: &ExtensionConfig) -> Option<u64> {
+    match config {
+        ExtensionConfig::Sse { timeout, .. }
+        | ExtensionConfig::StreamableHttp { timeout, .. }
+        | ExtensionConfig::Stdio { timeout, .. }
+        | ExtensionConfig::Builtin { timeout, .. }
+        | ExtensionConfig::InlinePython { timeout, .. } => *timeout,
+        ExtensionConfig::Platform { .. }
+        | ExtensionConfig::Frontend { .. }
+        | ExtensionConfig::Unresolved { .. } => None,
+    }
+}
+
+/// Sets the timeout of the variants that have one; the others are left unchanged.
+fn set_extension_timeout(config: &mut ExtensionConfig, value: u64) {
+    match config {
+        ExtensionConfig::Sse { timeout, .. }
+        | ExtensionConfig::StreamableHttp { timeout, .. }
+        | ExtensionConfig::Stdio { timeout, .. }
+        | ExtensionConfig::Builtin { timeout, .. }
+        | ExtensionConfig::InlinePython { timeout, .. } => *timeout = Some(value),
+        ExtensionConfig::Platform { .. }
+        | ExtensionConfig::Frontend { .. }
+        | ExtensionConfig::Unresolved { .. } => {}
+    }
+}
+
+/// The effective timeout of the extension, including any runtime override. `None` if the
+/// extension isn't configured or its type has no timeout.
+pub fn get_extension_timeout(key: &str) -> Option<u64> {
+    get_extensions_map()
+        .get(key)
+        .and_then(|entry| extension_timeout(&entry.config))
+}
+
+fn validate_uri(uri: &str) -> Result<(), ExtensionValidationError> {
+    url::Url::parse(uri)
+        .map(|_| ())
+        .map_err(|e| ExtensionValidationError::InvalidUri {
+            uri: uri.to_string(),
+            reason: e.to_string(),
+        })
+}
+
+/// Hash of the stored extensions and groups, for detecting edits made behind our back.
+/// Object keys are sorted before hashing so the value doesn't depend on serialization order.
+pub fn extensions_config_fingerprint() -> u64 {
+    let config = Config::global();
+    let extensions = config
+        .get_param::<Value>(EXTENSIONS_CONFIG_KEY)
+        .unwrap_or(Value::Null);
+    let groups = config
+        .get_param::<Value>(EXTENSION_GROUPS_CONFIG_KEY)
+        .unwrap_or(Value::Null);
+    fingerprint_values(&extensions, &groups)
+}
+
+fn fingerprint_values(extensions: &Value, groups: &Value) -> u64 {
+    let canonical = Value::Array(vec![
+        canonicalize_json(extensions),
+        canonicalize_json(groups),
+    ]);
+    let hash = blake3::hash(canonical.to_string().as_bytes());
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&hash.as_bytes()[..8]);
+    u64::from_le_bytes(bytes)
+}
+
+fn canonicalize_json(value: &Value) -> Value {
+    match value {
+        Value::Object(obj) => {
+            let mut keys: Vec<&String> = obj.keys().collect();
+            keys.sort();
+            Value::Object(
+                keys.into_iter()
+                    .map(|k| (k.clone(), canonicalize_json(&obj[k])))
+                    .collect(),
+            )
+        }
+        Value::Array(items) => Value::Array(items.iter().map(canonicalize_json).collect()),
+        other => other.clone(),
+    }
+}
+
+/// Optimistic-concurrency save: writes `extensions` only if the stored config still has the
+/// fingerprint the caller observed when it read it, failing with `ExtensionError::Conflict`
+/// otherwise.
+pub fn save_extensions_map_if_unchanged(
+    expected_fingerprint: u64,
+    extensions: HashMap<String, ExtensionEntry>,
+) -> Result<(), ExtensionError> {
+    let actual = extensions_config_fingerprint();
+    if actual != expected_fingerprint {
+        return Err(ConfigConflictError {
+            expected: expected_fingerprint,
+            actual,
+        }
+        .into());
+    }
+    save_extensions_map(extensions)?;
+    Ok(())
+}
+
+/// Visits every extension in the loaded map without collecting them.
+pub fn for_each_extension<F: FnMut(&str, &ExtensionEntry)>(mut f: F) {
+    for (key, entry) in &get_extensions_map() {
+        f(key, entry);
+    }
+}
+
+/// Returns the first extension matching `pred`, stopping as soon as one is found.
+pub fn find_extension<F: FnMut(&ExtensionEntry) -> bool>(pred: F) -> Option<ExtensionEntry> {
+    get_extensions_map().into_values().find(pred)
+}
+
+/// If several keys share the display name, the lexicographically smallest key wins so the
+/// result doesn't depend on map iteration order.
+pub fn get_extension_by_name(name: &str) -> Option<ExtensionConfig> {
+    get_extensions_map()
+        .into_iter()
+        .filter(|(_, entry)| entry.config.name() == name)
+        .min_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(_, entry)| entry.config)
+}
+
+/// Largest edit distance at which a display name still counts as a fuzzy match.
+const FUZZY_MATCH_MAX_DISTANCE: usize = 2;
+
+/// Extensions whose display name is close to `name`, best candidate first, for "did you
+/// mean" hints. Matching ignores case; names starting with `name` rank ahead of the rest,
+/// which are ordered by edit distance. Unlike `get_extension_by_name` this never requires
+/// an exact match.
+pub fn find_extension_fuzzy(name: &str) -> Vec<(String, ExtensionEntry)> {
+    rank_fuzzy_matches(name, get_extensions_map())
+}
+
+fn rank_fuzzy_matches(
+    name: &str,
+    extensions: HashMap<String, ExtensionEntry>,
+) -> Vec<(String, ExtensionEntry)> {
+    let query = name.to_lowercase();
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    let mut ranked: Vec<((bool, usize), String, ExtensionEntry)> = extensions
+        .into_iter()
+        .filter_map(|(key, entry)| {
+            let candidate = entry.config.name().to_lowercase();
+            let is_prefix = candidate.starts_with(&query);
+            let distance = levenshtein(&query, &candidate);
+            (is_prefix || distance <= FUZZY_MATCH_MAX_DISTANCE).then_some((
+                (!is_prefix, distance),
+                key,
+                entry,
+            ))
+        })
+        .collect();
+    ranked.sort_by(|(a, a_key, _), (b, b_key, _)| a.cmp(b).then_with(|| a_key.cmp(b_key)));
+    ranked
+        .into_iter()
+        .map(|(_, key, entry)| (key, entry))
+        .collect()
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, a_char) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+pub fn extension_exists_by_name(name: &str) -> bool {
+    get_extensions_map()
+        .values()
+        .any(|entry| entry.config.name() == name)
+}
+
+/// The key of the extension called `name`, resolved the same way as
+/// `get_extension_by_name` when several keys share the name.
+pub fn extension_key_for_name(name: &str) -> Option<String> {
+    key_for_name(&get_extensions_map(), name)
+}
+
+/// The smallest key among the extensions named `name`.
+fn key_for_name(extensions: &HashMap<String, ExtensionEntry>, name: &str) -> Option<String> {
+    extensions
+        .iter()
+        .filter(|(_, entry)| entry.config.name() == name)
+        .map(|(key, _)| key)
+        .min()
+        .cloned()
+}
+
+/// Display names used by more than one key, mapped to those keys in sorted order.
+pub fn find_duplicate_names() -> HashMap<String, Vec<String>> {
+    duplicate_names(&get_extensions_map())
+}
+
+fn duplicate_names(extensions: &HashMap<String, ExtensionEntry>) -> HashMap<String, Vec<String>> {
+    let mut by_name: HashMap<String, Vec<String>> = HashMap::new();
+    for (key, entry) in extensions {
+        by_name
+            .entry(entry.config.name())
+            .or_default()
+            .push(key.clone());
+    }
+    by_name.retain(|_, keys| keys.len() > 1);
+    for keys in by_name.values_mut() {
+        keys.sort();
+    }
+    by_name
+}
+
+pub fn set_extension(entry: ExtensionEntry) -> Result<(), SaveError> {
+    set_extension_in_layer(entry, ExtensionLayer::Global)
+}
+
+/// Adds a new extension, refusing to overwrite one already stored under the same key unless
+/// that one is only an `Unresolved` placeholder.
+pub fn create_extension(entry: ExtensionEntry) -> Result<(), ExtensionError> {
+    if !is_valid_extension_name(&entry.config.name()) {
+        return Err(ExtensionError::EmptyKey(entry.config.name()));
+    }
+    let key = entry.config.key();
+    if get_extensions_layer(ExtensionLayer::Global)
+        .get(&key)
+        .is_some_and(|existing| !is_placeholder(existing))
+    {
+        return Err(ExtensionError::AlreadyExists(key));
+    }
+    set_extension(entry)?;
+    Ok(())
+}
+
+/// Replaces an existing extension, refusing to create one that isn't stored yet.
+pub fn update_extension(entry: ExtensionEntry) -> Result<(), ExtensionError> {
+    let key = entry.config.key();
+    if !get_extensions_layer(ExtensionLayer::Global).contains_key(&key) {
+        return Err(ExtensionError::NotFound(key));
+    }
+    set_extension(entry)?;
+    Ok(())
+}
+
+/// Validates the entry and enforces `max_extensions` before persisting it. `set_extension`
+/// skips these checks, e.g. for migrations.
+pub fn set_extension_checked(entry: ExtensionEntry) -> Result<(), ExtensionError> {
+    if !is_valid_extension_name(&entry.config.name()) {
+        return Err(ExtensionError::EmptyKey(entry.config.name()));
+    }
+    validate_extension_config(&entry.config)?;
+    let key = entry.config.key();
+    if get_extension_groups_map().contains_key(&key) {
+        return Err(ExtensionError::KeyConflict(key));
+    }
+    check_capacity(
+        &read_global_extensions(),
+        &entry.config.key(),
+        get_max_extensions(),
+    )?;
+    set_extension(entry)?;
+    Ok(())
+}
+
+/// The configured cap on the number of stored extensions, not counting platform extensions;
+/// `None` (the default) is unlimited.
+pub fn get_max_extensions() -> Option<usize> {
+    Config::global()
+        .get_param::<usize>(MAX_EXTENSIONS_CONFIG_KEY)
+        .ok()
+}
+
+pub fn set_max_extensions(limit: Option<usize>) -> Result<(), SaveError> {
+    let config = Config::global();
+    let result = match limit {
+        Some(limit) => config.set_param(MAX_EXTENSIONS_CONFIG_KEY, Value::from(limit)),
+        None => config.delete(MAX_EXTENSIONS_CONFIG_KEY),
+    };
+    result.map_err(|e| setting_write_error(MAX_EXTENSIONS_CONFIG_KEY, e))
+}
+
+/// Overwriting an existing key never grows the map, so it's allowed even at capacity.
+/// Platform extensions are left out of the count, since whether they are stored depends
+/// only on whether anything else has been saved yet.
+fn check_capacity(
+    extensions: &HashMap<String, ExtensionEntry>,
+    key: &str,
+    limit: Option<usize>,
+) -> Result<(), ExtensionError> {
+    let counted = |key: &str| !PLATFORM_EXTENSIONS.contains_key(key);
+    let count = extensions.keys().filter(|key| counted(key)).count();
+    match limit {
+        Some(limit) if counted(key) && !extensions.contains_key(key) && count >= limit => {
+            Err(ExtensionError::CapacityExceeded {
+                key: key.to_string(),
+                limit,
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// `check_capacity` for a whole map about to replace the stored one. The first key past the
+/// limit, in key order, is the one reported.
+fn check_total_capacity(
+    extensions: &HashMap<String, ExtensionEntry>,
+    limit: Option<usize>,
+) -> Result<(), ExtensionError> {
+    let Some(limit) = limit else {
+        return Ok(());
+    };
+    let mut counted: Vec<&String> = extensions
+        .keys()
+        .filter(|key| !PLATFORM_EXTENSIONS.contains_key(key.as_str()))
+        .collect();
+    counted.sort();
+    match counted.get(limit) {
+        Some(key) => Err(ExtensionError::CapacityExceeded {
+            key: key.to_string(),
+            limit,
+        }),
+        None => Ok(()),
+    }
+}
+
+pub fn set_extension_in_layer(
+    mut entry: ExtensionEntry,
+    layer: ExtensionLayer,
+) -> Result<(), SaveError> {
+    let mut extensions = get_extensions_layer(layer);
+    let key = entry.config.key();
+    if get_extension_groups_map().contains_key(&key) {
+        warn!(key = %key, "Extension key is also used by an extension group");
+    }
+    entry.updated_at = Some(now_unix());
+    extensions.insert(key, entry);
+    save_extensions_layer(layer, extensions)
+}
+
+pub fn remove_extension(key: &str) -> Result<(), SaveError> {
+    let mut extensions = get_extensions_layer(ExtensionLayer::Global);
+    extensions.remove(key);
+    save_extensions_map(extensions)
+}
+
+/// Removes every stored extension in `keys` with a single read and save, returning the keys
+/// that weren't stored. With `cascade_groups` the removed keys are also dropped from every
+/// group's explicit members, and both maps are written together as one undoable change.
+pub fn remove_extensions(keys: &[String], cascade_groups: bool) -> Result<Vec<String>, SaveError> {
+    let mut extensions = get_extensions_layer(ExtensionLayer::Global);
+    let mut groups = get_extension_groups_map();
+    let (removed, missing) = remove_keys(keys, &mut extensions, &mut groups, cascade_groups);
+    if removed.is_empty() {
+        return Ok(missing);
+    }
+    if removed.groups_changed {
+        save_extensions_and_groups(extensions, groups)?;
+    } else {
+        save_extensions_map(extensions)?;
+    }
+    Ok(missing)
+}
+
+/// What `remove_keys` took out of the maps.
+#[derive(Debug, Default)]
+struct RemovedKeys {
+    extensions: Vec<String>,
+    groups_changed: bool,
+}
+
+impl RemovedKeys {
+    fn is_empty(&self) -> bool {
+        self.extensions.is_empty()
+    }
+}
+
+/// Returns what was removed and the keys that weren't in `extensions`, both in `keys` order.
+fn remove_keys(
+    keys: &[String],
+    extensions: &mut HashMap<String, ExtensionEntry>,
+    groups: &mut HashMap<String, ExtensionGroup>,
+    cascade_groups: bool,
+) -> (RemovedKeys, Vec<String>) {
+    let mut removed = RemovedKeys::default();
+    let mut missing = Vec::new();
+    for key in keys {
+        if extensions.remove(key).is_some() {
+            removed.extensions.push(key.clone());
+        } else if !removed.extensions.contains(key) && !missing.contains(key) {
+            missing.push(key.clone());
+        }
+    }
+    if cascade_groups {
+        for group in groups.values_mut() {
+            let before = group.extension_keys.len();
+            group
+                .extension_keys
+                .retain(|member| !removed.extensions.contains(member));
+            removed.groups_changed |= group.extension_keys.len() != before;
+        }
+    }
+    (removed, missing)
+}
+
+/// Sets the extension's `enabled` flag. If it belongs to an `all_or_nothing` group, the rest
+/// of that group (and of any such group those members belong to) follows in the same save.
+/// Enabling such a group is refused with `PlaceholderMembers` while any of those members is
+/// still an `Unresolved` placeholder, since the group couldn't run as a whole. Returns
+/// `NotFound` if the global layer holds no extension under `key`.
+pub fn set_extension_enabled(key: &str, enabled: bool) -> Result<(), ExtensionError> {
+    let mut extensions = get_extensions_layer(ExtensionLayer::Global);
+    let groups = get_extension_groups_map();
+    set_enabled_linked(key, enabled, &groups, &mut extensions, now_unix())?;
+    save_extensions_map(extensions)?;
+    Ok(())
+}
+
+fn set_enabled_linked(
+    key: &str,
+    enabled: bool,
+    groups: &HashMap<String, ExtensionGroup>,
+    extensions: &mut HashMap<String, ExtensionEntry>,
+    now: i64,
+) -> Result<(), ExtensionError> {
+    if !extensions.contains_key(key) {
+        return Err(ExtensionError::NotFound(key.to_string()));
+    }
+    let linked = all_or_nothing_linked(key, groups, extensions);
+    let members: Vec<&str> = std::iter::once(key)
+        .chain(linked.iter().map(String::as_str))
+        .collect();
+    if enabled && !linked.is_empty() {
+        let placeholders: Vec<String> = members
+            .iter()
+            .filter(|member| extensions.get(**member).is_some_and(is_placeholder))
+            .map(|member| member.to_string())
+            .collect();
+        if !placeholders.is_empty() {
+            return Err(ExtensionError::PlaceholderMembers {
+                key: key.to_string(),
+                placeholders,
+            });
+        }
+    }
+    for member in members {
+        if let Some(entry) = extensions.get_mut(member) {
+            if member == key || entry.enabled != enabled {
+                entry.enabled = enabled;
+                entry.updated_at = Some(now);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Like `set_extension_enabled`, addressing the extension by display name (resolved as in
+/// `get_extension_by_name`, but among the global layer that gets written). Returns whether
+/// it was enabled before, or `NotFound` if no stored extension has that name.
+pub fn set_extension_enabled_by_name(name: &str, enabled: bool) -> Result<bool, ExtensionError> {
+    let extensions = get_extensions_layer(ExtensionLayer::Global);
+    let key = key_for_name(&extensions, name)
+        .ok_or_else(|| ExtensionError::NotFound(name.to_string()))?;
+    let previous = extensions[&key].enabled;
+    set_extension_enabled(&key, enabled)?;
+    Ok(previous)
+}
+
+/// Hides `tool` of the extension stored under `extension_key`, leaving the extension enabled.
+/// Nothing is written if the tool is already disabled. An extension that is already running
+/// picks the change up the next time it is added to an `ExtensionManager`, or through
+/// `ExtensionManager::set_disabled_tools`.
+pub fn disable_tool(extension_key: &str, tool: &str) -> Result<(), ExtensionError> {
+    set_tool_disabled(extension_key, tool, true)
+}
+
+/// Undoes `disable_tool`. Nothing is written if the tool isn't disabled.
+pub fn enable_tool(extension_key: &str, tool: &str) -> Result<(), ExtensionError> {
+    set_tool_disabled(extension_key, tool, false)
+}
+
+/// The tools disabled on the extension, which `ExtensionManager` leaves out of the tool list
+/// while the extension runs. Empty if the extension isn't configured.
+pub fn get_disabled_tools(extension_key: &str) -> Vec<String> {
+    get_extensions_map()
+        .remove(extension_key)
+        .map(|entry| entry.disabled_tools)
+        .unwrap_or_default()
+}
+
+fn set_tool_disabled(
+    extension_key: &str,
+    tool: &str,
+    disabled: bool,
+) -> Result<(), ExtensionError> {
+    let mut extensions = get_extensions_layer(ExtensionLayer::Global);
+    let entry = extensions
+        .get_mut(extension_key)
+        .ok_or_else(|| ExtensionError::NotFound(extension_key.to_string()))?;
+    if toggle_disabled_tool(&mut entry.disabled_tools, tool, disabled) {
+        entry.updated_at = Some(now_unix());
+        save_extensions_map(extensions)?;
+    }
+    Ok(())
+}
+
+/// Adds or removes `tool` in the list, returning whether it changed.
+fn toggle_disabled_tool(disabled_tools: &mut Vec<String>, tool: &str, disabled: bool) -> bool {
+    let present = disabled_tools.iter().any(|t| t == tool);
+    match (disabled, present) {
+        (true, false) => disabled_tools.push(tool.to_string()),
+        (false, true) => disabled_tools.retain(|t| t != tool),
+        _ => return false,
+    }
+    true
+}
+
+/// Every other extension that must share `key`'s state through `all_or_nothing` groups,
+/// following chains of such groups, sorted.
+fn all_or_nothing_linked(
+    key: &str,
+    groups: &HashMap<String, ExtensionGroup>,
+    extensions: &HashMap<String, ExtensionEntry>,
+) -> Vec<String> {
+    let mut linked: HashSet<String> = HashSet::from([key.to_string()]);
+    let mut pending = vec![key.to_string()];
+    while let Some(current) = pending.pop() {
+        for group in groups.values() {
+            if !group.all_or_nothing || !group.contains_key(&current) {
+                continue;
+            }
+            for member in group.resolve_members(extensions) {
+                if linked.insert(member.clone()) {
+                    pending.push(member);
+                }
+            }
+        }
+    }
+    linked.remove(key);
+    let mut linked: Vec<String> = linked.into_iter().collect();
+    linked.sort();
+    linked
+}
+
+/// Replaces the stored extensions with exactly `extensions`, holding every entry to the
+/// checks of `set_extension_checked`. Entries whose name gives no key, that are stored under
+/// a key other than their own, or that fail validation are returned together and nothing is
+/// written. Every entry written is stamped with `updated_at`.
+pub fn replace_all_extensions(
+    mut extensions: HashMap<String, ExtensionEntry>,
+) -> Result<(), ExtensionError> {
+    let invalid = invalid_extension_keys(&extensions);
+    if !invalid.is_empty() {
+        return Err(ExtensionError::InvalidEntries(invalid));
+    }
+    let groups = get_extension_groups_map();
+    let mut keys: Vec<&String> = extensions.keys().collect();
+    keys.sort();
+    if let Some(key) = keys.into_iter().find(|key| groups.contains_key(*key)) {
+        return Err(ExtensionError::KeyConflict(key.clone()));
+    }
+    check_total_capacity(&extensions, get_max_extensions())?;
+    let now = now_unix();
+    for entry in extensions.values_mut() {
+        entry.updated_at = Some(now);
+    }
+    save_extensions_map(extensions)?;
+    Ok(())
+}
+
+fn invalid_extension_keys(extensions: &HashMap<String, ExtensionEntry>) -> Vec<String> {
+    let mut invalid: Vec<String> = extensions
+        .iter()
+        .filter_map(|(key, entry)| {
+            let problem = if !is_valid_extension_name(&entry.config.name()) {
+                ExtensionError::EmptyKey(entry.config.name()).to_string()
+            } else if entry.config.key() != *key {
+                format!(
+                    "stored under {key}, but its name gives {}",
+                    entry.config.key()
+                )
+            } else {
+                validate_extension_config(&entry.config).err()?.to_string()
+            };
+            warn!(extension = %key, error = %problem, "Rejecting invalid extension");
+            Some(key.clone())
+        })
+        .collect();
+    invalid.sort();
+    invalid
+}
+
+/// Applies a JSON merge patch (RFC 7386) to the flattened entry, e.g.
+/// `{"timeout": 600}` or `{"enabled": false}`, then re-validates and saves it. A `null`
+/// value removes the field. The patch may not rename the extension, since that would
+/// change its key.
+pub fn patch_extension(key: &str, patch: Value) -> Result<ExtensionEntry, ExtensionError> {
+    if !patch.is_object() {
+        return Err(ExtensionError::Invalid(
+            "patch must be a JSON object".to_string(),
+        ));
+    }
+
+    let mut extensions = get_extensions_layer(ExtensionLayer::Global);
+    let current = extensions
+        .get(key)
+        .ok_or_else(|| ExtensionError::NotFound(key.to_string()))?;
+
+    let mut value =
+        serde_json::to_value(current).map_err(|e| ExtensionError::Invalid(e.to_string()))?;
+    merge_json_patch(&mut value, &patch);
+    let mut patched: ExtensionEntry =
+        serde_json::from_value(value).map_err(|e| ExtensionError::Invalid(e.to_string()))?;
+
+    if patched.config.key() != key {
+        return Err(ExtensionError::Invalid(format!(
+            "patch would change the key of {key} to {}",
+            patched.config.key()
+        )));
+    }
+    validate_extension_config(&patched.config)?;
+    patched.updated_at = Some(now_unix());
+
+    extensions.insert(key.to_string(), patched.clone());
+    save_extensions_map(extensions)?;
+    Ok(patched)
+}
+
+fn merge_json_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch_obj) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(serde_json::Map::new());
+    }
+    if let Value::Object(target_obj) = target {
+        for (k, v) in patch_obj {
+            if v.is_null() {
+                target_obj.remove(k);
+            } else {
+                merge_json_patch(target_obj.entry(k.clone()).or_insert(Value::Null), v);
+            }
+        }
+    }
+}
+
+/// Copies `source_key` under `new_name` as a disabled entry, leaving the original untouched.
+/// A `new_name` that gives no key is refused with `EmptyKey`.
+pub fn duplicate_extension(
+    source_key: &str,
+    new_name: &str,
+) -> Result<ExtensionEntry, ExtensionError> {
+    if !is_valid_extension_name(new_name) {
+        return Err(ExtensionError::EmptyKey(new_name.to_string()));
+    }
+    let mut extensions = get_extensions_layer(ExtensionLayer::Global);
+    let source = extensions
+        .get(source_key)
+        .ok_or_else(|| ExtensionError::NotFound(source_key.to_string()))?;
+
+    let new_key = name_to_key(new_name);
+    if extensions.contains_key(&new_key) {
+        return Err(ExtensionError::AlreadyExists(new_key));
+    }
+
+    let mut copy = source.clone();
+    copy.config.set_name(new_name);
+    copy.enabled = false;
+    copy.updated_at = Some(now_unix());
+
+    extensions.insert(new_key, copy.clone());
+    save_extensions_map(extensions)?;
+    Ok(copy)
+}
+
+/// Focus mode: enables exactly `keys` and disables every other extension in one save.
+/// Bundled platform extensions stay enabled unless `force` is set. Nothing is written if
+/// any of `keys` is unknown.
+pub fn set_exclusive_enabled(keys: &[String], force: bool) -> Result<(), ExtensionGroupError> {
+    let mut extensions = get_extensions_layer(ExtensionLayer::Global);
+
+    let unknown: Vec<String> = keys
+        .iter()
+        .filter(|key| !extensions.contains_key(*key))
+        .cloned()
+        .collect();
+    if !unknown.is_empty() {
+        return Err(ExtensionGroupError::UnknownExtensions(unknown));
+    }
+
+    apply_exclusive_enabled(keys, &mut extensions, force);
+    save_extensions_map(extensions)?;
+    Ok(())
+}
+
+fn apply_exclusive_enabled(
+    keys: &[String],
+    extensions: &mut HashMap<String, ExtensionEntry>,
+    force: bool,
+) {
+    let now = now_unix();
+    for (key, entry) in extensions.iter_mut() {
+        let enabled = if keys.contains(key) {
+            true
+        } else if force || !is_bundled_platform(entry) {
+            false
+        } else {
+            continue;
+        };
+        if entry.enabled != enabled {
+            entry.enabled = enabled;
+            entry.updated_at = Some(now);
+        }
+    }
+}
+
+fn is_bundled_platform(entry: &ExtensionEntry) -> bool {
+    matches!(
+        entry.config,
+        ExtensionConfig::Platform {
+            bundled: Some(true),
+            ..
+        }
+    )
+}
+
+/// Whether `key` is a bundled platform extension rather than one the user added.
+pub fn is_extension_bundled(key: &str) -> bool {
+    get_extensions_map()
+        .get(key)
+        .is_some_and(is_bundled_platform)
+}
+
+/// Every extension except the bundled platform ones.
+pub fn get_user_extensions() -> Vec<ExtensionEntry> {
+    get_extensions_map()
+        .into_values()
+        .filter(|entry| !is_bundled_platform(entry))
+        .collect()
+}
+
+pub fn get_all_extensions() -> Vec<ExtensionEntry> {
+    let extensions = get_extensions_map();
+    extensions.into_values().collect()
+}
+
+/// All extensions ordered by display name (case-insensitive), then key, for stable listings.
+pub fn get_all_extensions_sorted() -> Vec<ExtensionEntry> {
+    let mut entries: Vec<(String, ExtensionEntry)> = get_extensions_map().into_iter().collect();
+    sort_by_name(&mut entries);
+    entries.into_iter().map(|(_, entry)| entry).collect()
+}
+
+fn sort_by_name(entries: &mut [(String, ExtensionEntry)]) {
+    entries.sort_by_cached_key(|(key, entry)| (entry.config.name().to_lowercase(), key.clone()));
+}
+
+pub fn get_all_extension_names() -> Vec<String> {
+    let mut names = Vec::new();
+    for_each_extension(|key, _| names.push(key.to_string()));
+    names
+}
+
+/// The extension's own `enabled` flag; see `effective_enabled` for the state after group masks.
+pub fn is_extension_enabled(key: &str) -> bool {
+    let extensions = get_extensions_map();
+    extensions.get(key).map(|e| e.enabled).unwrap_or(false)
+}
+
+/// Whether the extension is enabled and not masked by a switched-off `Mask` group.
+pub fn effective_enabled(key: &str) -> bool {
+    is_effectively_enabled(key, &get_extensions_map(), &get_extension_groups_map())
+}
+
+fn is_effectively_enabled(
+    key: &str,
+    extensions: &HashMap<String, ExtensionEntry>,
+    groups: &HashMap<String, ExtensionGroup>,
+) -> bool {
+    extensions.get(key).is_some_and(|entry| entry.enabled)
+        && !groups
+            .values()
+            .any(|group| group.is_masking() && group.contains_key(key))
+}
+
+pub fn get_enabled_extensions() -> Vec<ExtensionConfig> {
+    extensions_by_effective_state(true)
+}
+
+/// The counterpart of `get_enabled_extensions`: every extension that is configured but off.
+pub fn get_disabled_extensions() -> Vec<ExtensionConfig> {
+    extensions_by_effective_state(false)
+}
+
+fn extensions_by_effective_state(enabled: bool) -> Vec<ExtensionConfig> {
+    let extensions = get_extensions_map();
+    let groups = get_extension_groups_map();
+    extensions
+        .iter()
+        .filter(|(key, _)| is_effectively_enabled(key, &extensions, &groups) == enabled)
+        .map(|(_, entry)| entry.config.clone())
+        .collect()
+}
+
+/// Most recently changed first; entries never stamped come last, then ties break by key.
+pub fn get_extensions_sorted_by_recency() -> Vec<ExtensionEntry> {
+    let mut entries: Vec<(String, ExtensionEntry)> = get_extensions_map().into_iter().collect();
+    sort_by_recency(&mut entries);
+    entries.into_iter().map(|(_, entry)| entry).collect()
+}
+
+fn sort_by_recency(entries: &mut [(String, ExtensionEntry)]) {
+    entries.sort_by(|(a_key, a), (b_key, b)| {
+        b.updated_at
+            .cmp(&a.updated_at)
+            .then_with(|| a_key.cmp(b_key))
+    });
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExtensionSort {
+    /// As `get_all_extensions_sorted`.
+    #[default]
+    Name,
+    /// As `get_extensions_sorted_by_recency`.
+    Recency,
+    /// Enabled extensions before disabled ones, each ordered by name.
+    EnabledFirst,
+}
+
+/// One window of the sorted extensions, along with how many there are in total.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ExtensionPage {
+    pub entries: Vec<ExtensionEntry>,
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
+}
+
+/// Up to `limit` extensions starting at `offset` in the given order. Every order breaks ties
+/// by key, so consecutive pages neither repeat nor skip entries while the config is
+/// unchanged. An `offset` past the end yields an empty page.
+pub fn get_extensions_page(offset: usize, limit: usize, sort: ExtensionSort) -> ExtensionPage {
+    extensions_page(get_extensions_map(), offset, limit, sort)
+}
+
+fn extensions_page(
+    extensions: HashMap<String, ExtensionEntry>,
+    offset: usize,
+    limit: usize,
+    sort: ExtensionSort,
+) -> ExtensionPage {
+    let mut entries: Vec<(String, ExtensionEntry)> = extensions.into_iter().collect();
+    match sort {
+        ExtensionSort::Name => sort_by_name(&mut entries),
+        ExtensionSort::Recency => sort_by_recency(&mut entries),
+        ExtensionSort::EnabledFirst => {
+            sort_by_name(&mut entries);
+            // Stable, so the name order holds within each half.
+            entries.sort_by_key(|(_, entry)| !entry.enabled);
+        }
+    }
+    ExtensionPage {
+        total: entries.len(),
+        entries: entries
+            .into_iter()
+            .skip(offset)
+            .take(limit)
+            .map(|(_, entry)| entry)
+            .collect(),
+        offset,
+        limit,
+    }
+}
+
+pub fn count_extensions() -> usize {
+    get_extensions_map().len()
+}
+
+pub fn count_enabled_extensions() -> usize {
+    let extensions = get_extensions_map();
+    let groups = get_extension_groups_map();
+    extensions
+        .keys()
+        .filter(|key| is_effectively_enabled(key, &extensions, &groups))
+        .count()
+}
+
+/// Enabled extensions in startup order: by `load_order` (unset last), then by key.
+pub fn get_enabled_extensions_ordered() -> Vec<ExtensionConfig> {
+    let extensions = get_extensions_map();
+    let groups = get_extension_groups_map();
+    let mut enabled: Vec<(String, ExtensionEntry)> = extensions
+        .iter()
+        .filter(|(key, _)| is_effectively_enabled(key, &extensions, &groups))
+        .map(|(key, entry)| (key.clone(), entry.clone()))
+        .collect();
+    sort_by_load_order(&mut enabled);
+    enabled.into_iter().map(|(_, entry)| entry.config).collect()
+}
+
+fn sort_by_load_order(entries: &mut [(String, ExtensionEntry)]) {
+    entries.sort_by(|(a_key, a), (b_key, b)| {
+        match (a.load_order, b.load_order) {
+            (Some(a_order), Some(b_order)) => a_order.cmp(&b_order),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        }
+        .then_with(|| a_key.cmp(b_key))
+    });
+}
+
+// Async wrappers that move the blocking config file I/O off the Tokio worker threads.
+
+pub async fn get_extensions_map_async() -> Result<HashMap<String, ExtensionEntry>, JoinError> {
+    tokio::task::spawn_blocking(get_extensions_map).await
+}
+
+pub async fn save_extensions_map_async(
+    extensions: HashMap<String, ExtensionEntry>,
+) -> Result<Result<(), SaveError>, JoinError> {
+    tokio::task::spawn_blocking(move || save_extensions_map(extensions)).await
+}
+
+pub async fn get_enabled_extensions_async() -> Result<Vec<ExtensionConfig>, JoinError> {
+    tokio::task::spawn_blocking(get_enabled_extensions).await
+}
+
+#[tracing::instrument(
+    name = "extension_groups.load",
+    level = "debug",
+    skip_all,
+    fields(entries, malformed_skipped, elapsed_ms)
+)]
+fn get_extension_groups_map() -> HashMap<String, ExtensionGroup> {
+    let start = Instant::now();
+    take_malformed_skipped();
+    let groups = read_extension_groups();
+    LAST_SEEN_KEYS.lock().unwrap().groups = Some(groups.keys().cloned().collect());
+    tracing::Span::current().record("malformed_skipped", take_malformed_skipped());
+    record_io_span(start, groups.len());
+    groups
+}
+
+fn read_extension_groups() -> HashMap<String, ExtensionGroup> {
+    let raw: Value = Config::global()
+        .get_param::<Value>(EXTENSION_GROUPS_CONFIG_KEY)
+        .unwrap_or_else(|_| Value::Object(serde_json::Map::new()));
+
+    match raw {
+        Value::Object(obj) => {
+            let mut m = HashMap::with_capacity(obj.len());
+            for (k, v) in obj {
+                match serde_json::from_value::<ExtensionGroup>(v) {
+                    Ok(group) => {
+                        m.insert(k, group);
+                    }
+                    Err(err) => {
+                        count_malformed_skipped();
+                        warn!(group = %k, error = %err, "Skipping malformed extension group");
+                    }
+                }
+            }
+            m
+        }
+        other => {
+            warn!(
+                "Expected object for {}, got {}. Using empty map.",
+                EXTENSION_GROUPS_CONFIG_KEY, other
+            );
+            HashMap::new()
+        }
+    }
+}
+
+#[tracing::instrument(
+    name = "extension_groups.save",
+    level = "debug",
+    skip_all,
+    fields(entries, elapsed_ms)
+)]
+fn save_extension_groups_map(groups: HashMap<String, ExtensionGroup>) -> Result<(), SaveError> {
+    let start = Instant::now();
+    let entries = groups.len();
+    let result = write_extension_groups(groups);
+    invalidate_group_state_cache();
+    record_io_span(start, entries);
+    result
+}
+
+fn write_extension_groups(groups: HashMap<String, ExtensionGroup>) -> Result<(), SaveError> {
+    let value = serde_json::to_value(groups).map_err(|e| {
+        tracing::debug!("Failed to serialize extension groups: {}", e);
+        SaveError::Serialize {
+            key: EXTENSION_GROUPS_CONFIG_KEY.to_string(),
+            error: e.to_string(),
+        }
+    })?;
+    let previous = read_raw_config_state();
+    Config::global()
+        .set_param(EXTENSION_GROUPS_CONFIG_KEY, value)
+        .map_err(|e| {
+            tracing::debug!("Failed to save extension groups config: {}", e);
+            SaveError::Write {
+                key: EXTENSION_GROUPS_CONFIG_KEY.to_string(),
+                error: e.to_string(),
+            }
+        })?;
+    push_undo_state(previous);
+    Ok(())
+}
+
+fn compute_group_state(
+    group: &ExtensionGroup,
+    extensions: &HashMap<String, ExtensionEntry>,
+) -> ExtensionGroupState {
+    if group.is_masking() {
+        return ExtensionGroupState::Disabled;
+    }
+    let members = group.resolve_members(extensions);
+    if members.is_empty() {
+        return ExtensionGroupState::Disabled;
+    }
+
+    let mut enabled = 0;
+    let mut disabled = 0;
+    for key in &members {
+        match extensions.get(key) {
+            Some(entry) if entry.enabled => enabled += 1,
+            Some(_) => disabled += 1,
+            None => return ExtensionGroupState::Mixed,
+        }
+    }
+
+    match (enabled, disabled) {
+        (_, 0) => ExtensionGroupState::Enabled,
+        (0, _) => ExtensionGroupState::Disabled,
+        _ => ExtensionGroupState::Mixed,
+    }
+}
+
+fn missing_group_members(
+    group: &ExtensionGroup,
+    extensions: &HashMap<String, ExtensionEntry>,
+) -> Vec<String> {
+    group
+        .extension_keys
+        .iter()
+        .filter(|key| !extensions.contains_key(*key))
+        .cloned()
+        .collect()
+}
+
+/// Stores the group under `group.key()`, with its members normalized by
+/// `normalize_group_members` so they match the keys in the extensions map.
+pub fn set_extension_group(mut group: ExtensionGroup) -> Result<(), SaveError> {
+    normalize_group_members(&mut group);
+    let key = group.key();
+    if get_extensions_map().contains_key(&key) {
+        warn!(key = %key, "Extension group key is also used by an extension");
+    }
+    let mut groups = get_extension_groups_map();
+    groups.insert(key, group);
+    save_extension_groups_map(groups)
+}
+
+/// Stores all `groups` with a single read and save, each keyed by `group.key()` with its
+/// members normalized by `normalize_group_members`. A later group with the same key
+/// overwrites an earlier one, as repeated `set_extension_group` calls would.
+pub fn set_extension_groups(groups: Vec<ExtensionGroup>) -> Result<(), SaveError> {
+    let extensions = get_extensions_map();
+    let mut stored = get_extension_groups_map();
+    for key in insert_groups(&mut stored, groups) {
+        if extensions.contains_key(&key) {
+            warn!(key = %key, "Extension group key is also used by an extension");
+        }
+    }
+    save_extension_groups_map(stored)
+}
+
+/// Rewrites the group's explicit members to canonical keys with `name_to_key`, keeping the
+/// first occurrence of any that turn out to be duplicates, and does the same for the group
+/// keys in `exclusive_with`. Patterns are left as written.
+pub fn normalize_group_members(group: &mut ExtensionGroup) {
+    group.extension_keys = canonical_keys(std::mem::take(&mut group.extension_keys));
+    group.exclusive_with = canonical_keys(std::mem::take(&mut group.exclusive_with));
+}
+
+fn canonical_keys(keys: Vec<String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    keys.into_iter()
+        .map(|key| name_to_key(&key))
+        .filter(|key| seen.insert(key.clone()))
+        .collect()
+}
+
+/// Returns the keys written, in order.
+fn insert_groups(
+    stored: &mut HashMap<String, ExtensionGroup>,
+    groups: Vec<ExtensionGroup>,
+) -> Vec<String> {
+    let mut keys = Vec::with_capacity(groups.len());
+    for mut group in groups {
+        normalize_group_members(&mut group);
+        let key = group.key();
+        stored.insert(key.clone(), group);
+        keys.push(key);
+    }
+    keys
+}
+
+/// Stores the group after adding a disabled `Unresolved` placeholder for every explicit
+/// member that has no extension yet, so templates can ship groups ahead of their extensions.
+/// Setting the real extension under the same key later replaces the placeholder. The
+/// placeholders and the group are written together as a single undoable change. Returns the
+/// keys of the placeholders created.
+pub fn set_extension_group_with_placeholders(
+    mut group: ExtensionGroup,
+) -> Result<Vec<String>, SaveError> {
+    normalize_group_members(&mut group);
+    let mut extensions = get_extensions_layer(ExtensionLayer::Global);
+    let created = add_placeholders(&group, &mut extensions);
+    if created.is_empty() {
+        set_extension_group(group)?;
+        return Ok(created);
+    }
+
+    let key = group.key();
+    if extensions.contains_key(&key) {
+        warn!(key = %key, "Extension group key is also used by an extension");
+    }
+    let mut groups = get_extension_groups_map();
+    groups.insert(key, group);
+    save_extensions_and_groups(extensions, groups)?;
+    Ok(created)
+}
+
+fn add_placeholders(
+    group: &ExtensionGroup,
+    extensions: &mut HashMap<String, ExtensionEntry>,
+) -> Vec<String> {
+    let mut created = Vec::new();
+    for key in &group.extension_keys {
+        if !extensions.contains_key(key) {
+            extensions.insert(
+                key.clone(),
+                ExtensionEntry {
+                    enabled: false,
+                    load_order: None,
+                    updated_at: Some(now_unix()),
+                    disabled_tools: Vec::new(),
+                    config: ExtensionConfig::unresolved(key.as_str()),
+                },
+            );
+            created.push(key.clone());
+        }
+    }
+    created
+}
+
+fn is_placeholder(entry: &ExtensionEntry) -> bool {
+    matches!(entry.config, ExtensionConfig::Unresolved { .. })
+}
+
+/// Like `set_extension_group`, but refuses a group whose key is already an extension key.
+pub fn try_set_extension_group(group: ExtensionGroup) -> Result<(), ExtensionGroupError> {
+    if !is_valid_extension_name(&group.name) {
+        return Err(ExtensionGroupError::EmptyKey(group.name));
+    }
+    let key = group.key();
+    if get_extensions_map().contains_key(&key) {
+        return Err(ExtensionGroupError::KeyConflict(key));
+    }
+    set_extension_group(group)?;
+    Ok(())
+}
+
+/// Reports whether `key` is used by an extension, a group, both, or neither.
+pub fn key_conflicts(key: &str) -> KeyConflict {
+    key_conflict(key, &get_extensions_map(), &get_extension_groups_map())
+}
+
+fn key_conflict(
+    key: &str,
+    extensions: &HashMap<String, ExtensionEntry>,
+    groups: &HashMap<String, ExtensionGroup>,
+) -> KeyConflict {
+    match (extensions.contains_key(key), groups.contains_key(key)) {
+        (true, true) => KeyConflict::Both,
+        (true, false) => KeyConflict::Extension,
+        (false, true) => KeyConflict::Group,
+        (false, false) => KeyConflict::None,
+    }
+}
+
+/// Like `set_extension_group`, but refuses to persist a group whose members don't all
+/// exist yet, returning the missing keys as `UnknownExtensions` so the caller can confirm or
+/// fix them.
+pub fn set_extension_group_checked(mut group: ExtensionGroup) -> Result<(), ExtensionGroupError> {
+    if !is_valid_extension_name(&group.name) {
+        return Err(ExtensionGroupError::EmptyKey(group.name));
+    }
+    normalize_group_members(&mut group);
+    let missing = missing_group_members(&group, &get_extensions_map());
+    if !missing.is_empty() {
+        warn!(
+            group = %group.name,
+            missing = ?missing,
+            "Extension group references unknown extensions"
+        );
+        return Err(ExtensionGroupError::UnknownExtensions(missing));
+    }
+
+    set_extension_group(group)?;
+    Ok(())
+}
+
+pub fn remove_extension_group(name: &str) -> Result<(), SaveError> {
+    let mut groups = get_extension_groups_map();
+    groups.remove(&name_to_key(name));
+    save_extension_groups_map(groups)
+}
+
+/// Moves a group to the key derived from `new_name`, keeping its members, in the same save
+/// that updates other groups' `exclusive_with`. Renaming to a name with the same key (e.g. a
+/// change in case) only updates the display name.
+pub fn rename_extension_group(old_name: &str, new_name: &str) -> Result<(), ExtensionGroupError> {
+    if !is_valid_extension_name(new_name) {
+        return Err(ExtensionGroupError::EmptyKey(new_name.to_string()));
+    }
+    let mut groups = get_extension_groups_map();
+    rename_group(&mut groups, old_name, new_name)?;
+    save_extension_groups_map(groups)?;
+    Ok(())
+}
+
+/// Moves the group in `groups` and points every other group's `exclusive_with` at its new
+/// key, so exclusivity keeps holding after the rename.
+fn rename_group(
+    groups: &mut HashMap<String, ExtensionGroup>,
+    old_name: &str,
+    new_name: &str,
+) -> Result<(), ExtensionGroupError> {
+    let old_key = name_to_key(old_name);
+    let new_key = name_to_key(new_name);
+
+    if new_key != old_key && groups.contains_key(&new_key) {
+        return Err(ExtensionGroupError::AlreadyExists(new_name.to_string()));
+    }
+
+    let mut group = groups
+        .remove(&old_key)
+        .ok_or_else(|| ExtensionGroupError::NotFound(old_name.to_string()))?;
+    group.name = new_name.to_string();
+    groups.insert(new_key.clone(), group);
+    if new_key != old_key {
+        for other in groups.values_mut() {
+            for key in other
+                .exclusive_with
+                .iter_mut()
+                .filter(|key| **key == old_key)
+            {
+                *key = new_key.clone();
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Adds `extension_key` to the group's explicit members. The key may also be given as a
+/// display name; it is stored via `name_to_key`. Nothing is written if it is already one of
+/// them.
+pub fn add_extension_to_group(
+    group_name: &str,
+    extension_key: &str,
+) -> Result<(), ExtensionGroupError> {
+    edit_group_members(group_name, |members| add_member(members, extension_key))
+}
+
+/// Removes `extension_key` (or the extension with that display name) from the group's
+/// explicit members. Nothing is written if it isn't one of them.
+pub fn remove_extension_from_group(
+    group_name: &str,
+    extension_key: &str,
+) -> Result<(), ExtensionGroupError> {
+    edit_group_members(group_name, |members| remove_member(members, extension_key))
+}
+
+fn add_member(members: &mut Vec<String>, extension_key: &str) -> bool {
+    let key = name_to_key(extension_key);
+    if members.iter().any(|member| name_to_key(member) == key) {
+        return false;
+    }
+    members.push(key);
+    true
+}
+
+fn remove_member(members: &mut Vec<String>, extension_key: &str) -> bool {
+    let key = name_to_key(extension_key);
+    let before = members.len();
+    members.retain(|member| name_to_key(member) != key);
+    members.len() != before
+}
+
+/// Applies `edit` to the group's `extension_keys` and saves the groups map if it reports a
+/// change.
+fn edit_group_members<F: FnOnce(&mut Vec<String>) -> bool>(
+    group_name: &str,
+    edit: F,
+) -> Result<(), ExtensionGroupError> {
+    let mut groups = get_extension_groups_map();
+    let group = groups
+        .get_mut(&name_to_key(group_name))
+        .ok_or_else(|| ExtensionGroupError::NotFound(group_name.to_string()))?;
+    if edit(&mut group.extension_keys) {
+        save_extension_groups_map(groups)?;
+    }
+    Ok(())
+}
+
+pub fn get_extension_group_by_name(name: &str) -> Option<ExtensionGroup> {
+    get_extension_group_by_key(&name_to_key(name))
+}
+
+/// Like `get_extension_group_by_name`, for callers that already hold the group key.
+pub fn get_extension_group_by_key(key: &str) -> Option<ExtensionGroup> {
+    get_extension_groups_map().remove(key)
+}
+
+pub fn get_all_extension_groups() -> Vec<ExtensionGroup> {
+    get_extension_groups_map().into_values().collect()
+}
+
+pub fn get_all_extension_group_names() -> Vec<String> {
+    get_extension_groups_map().into_keys().collect()
+}
+
+pub fn get_all_extension_group_names_sorted() -> Vec<String> {
+    let mut names = get_all_extension_group_names();
+    names.sort();
+    names
+}
+
+pub fn count_groups() -> usize {
+    get_extension_groups_map().len()
+}
+
+pub fn find_groups_containing(extension_key: &str) -> Vec<ExtensionGroup> {
+    get_extension_groups_map()
+        .into_values()
+        .filter(|group| group.contains_key(extension_key))
+        .collect()
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct GroupMember {
+    pub key: String,
+    /// `None` if the group references a key that isn't in the extensions map.
+    pub entry: Option<ExtensionEntry>,
+    pub enabled: bool,
+}
+
+/// Extensions that belong to no group, sorted by key. Bundled platform extensions are
+/// included unless `exclude_bundled` is set.
+pub fn get_ungrouped_extensions(exclude_bundled: bool) -> Vec<ExtensionEntry> {
+    ungrouped_extensions(
+        get_extensions_map(),
+        &get_extension_groups_map(),
+        exclude_bundled,
+    )
+}
+
+fn ungrouped_extensions(
+    extensions: HashMap<String, ExtensionEntry>,
+    groups: &HashMap<String, ExtensionGroup>,
+    exclude_bundled: bool,
+) -> Vec<ExtensionEntry> {
+    let grouped: HashSet<String> = groups
+        .values()
+        .flat_map(|group| group.resolve_members(&extensions))
+        .collect();
+    let mut ungrouped: Vec<(String, ExtensionEntry)> = extensions
+        .into_iter()
+        .filter(|(key, entry)| {
+            !grouped.contains(key) && !(exclude_bundled && is_bundled_platform(entry))
+        })
+        .collect();
+    ungrouped.sort_by(|(a, _), (b, _)| a.cmp(b));
+    ungrouped.into_iter().map(|(_, entry)| entry).collect()
+}
+
+/// For every extension, the keys of the groups it belongs to (sorted) and each group's state,
+/// computed from a single load of both maps. Extensions in no group map to an empty list.
+pub fn extension_group_membership() -> HashMap<String, Vec<(String, ExtensionGroupState)>> {
+    group_membership(&get_extensions_map(), &get_extension_groups_map())
+}
+
+fn group_membership(
+    extensions: &HashMap<String, ExtensionEntry>,
+    groups: &HashMap<String, ExtensionGroup>,
+) -> HashMap<String, Vec<(String, ExtensionGroupState)>> {
+    let mut membership: HashMap<String, Vec<(String, ExtensionGroupState)>> = extensions
+        .keys()
+        .map(|key| (key.clone(), Vec::new()))
+        .collect();
+    for (group_key, group) in groups {
+        let state = compute_group_state(group, extensions);
+        for member in group.resolve_members(extensions) {
+            if let Some(memberships) = membership.get_mut(&member) {
+                memberships.push((group_key.clone(), state));
+            }
+        }
+    }
+    for memberships in membership.values_mut() {
+        memberships.sort_by(|(a, _), (b, _)| a.cmp(b));
+    }
+    membership
+}
+
+/// Resolves every member of the group against a single load of the extensions map.
+pub fn get_group_members_detailed(group_name: &str) -> Option<Vec<GroupMember>> {
+    let group = get_extension_group_by_name(group_name)?;
+    let extensions = get_extensions_map();
+    Some(group_members_detailed(&group, &extensions))
+}
+
+fn group_members_detailed(
+    group: &ExtensionGroup,
+    extensions: &HashMap<String, ExtensionEntry>,
+) -> Vec<GroupMember> {
+    group
+        .resolve_members(extensions)
+        .into_iter()
+        .map(|key| {
+            let entry = extensions.get(&key).cloned();
+            let enabled = entry.as_ref().is_some_and(|e| e.enabled);
+            GroupMember {
+                key,
+                entry,
+                enabled,
+            }
+        })
+        .collect()
+}
+
+pub fn get_extension_group_state(name: &str) -> Option<ExtensionGroupState> {
+    get_extension_group_state_by_key(&name_to_key(name))
+}
+
+/// Like `get_extension_group_state`, for callers that already hold the group key.
+pub fn get_extension_group_state_by_key(key: &str) -> Option<ExtensionGroupState> {
+    let generation = {
+        let mut cache = GROUP_STATE_CACHE.lock().unwrap();
+        if cache.enabled {
+            if let Some(state) = cache.states.get(key) {
+                return Some(*state);
+            }
+        }
+        cache.generation
+    };
+
+    let group = get_extension_groups_map().remove(key)?;
+    let state = compute_group_state(&group, &get_extensions_map());
+    let mut cache = GROUP_STATE_CACHE.lock().unwrap();
+    if cache.enabled && cache.generation == generation {
+        cache.states.put(key.to_string(), state);
+    }
+    Some(state)
+}
+
+const GROUP_STATE_CACHE_CAPACITY: usize = 64;
+
+/// Recently computed group states, so group-detail screens asking about the same few groups
+/// don't re-read both maps each time. Cleared by every save through this module, by
+/// `reload_extensions` and by `watch_extensions_config` on every edit it sees, like
+/// `ExtensionIndex`. Off by default, since without a watcher running an edit made by
+/// another process would go unnoticed.
+struct GroupStateCache {
+    enabled: bool,
+    // Bumped on every invalidation so a state computed from maps that were saved over
+    // meanwhile is never stored.
+    generation: u64,
+    states: LruCache<String, ExtensionGroupState>,
+}
+
+impl GroupStateCache {
+    fn new() -> Self {
+        GroupStateCache {
+            enabled: false,
+            generation: 0,
+            states: LruCache::new(
+                NonZeroUsize::new(GROUP_STATE_CACHE_CAPACITY).expect("capacity is non-zero"),
+            ),
+        }
+    }
+
+    fn invalidate(&mut self) {
+        self.generation += 1;
+        self.states.clear();
+    }
+}
+
+static GROUP_STATE_CACHE: Lazy<Mutex<GroupStateCache>> =
+    Lazy::new(|| Mutex::new(GroupStateCache::new()));
+
+/// Turns the group state cache on or off. Hosts that turn it on should also keep a
+/// `watch_extensions_config` watcher running or call `reload_extensions` after outside
+/// edits. Switching it off also drops whatever is cached.
+pub fn set_group_state_cache_enabled(enabled: bool) {
+    let mut cache = GROUP_STATE_CACHE.lock().unwrap();
+    cache.enabled = enabled;
+    if !enabled {
+        cache.states.clear();
+    }
+}
+
+fn invalidate_group_state_cache() {
+    GROUP_STATE_CACHE.lock().unwrap().invalidate();
+}
+
+/// Every group's computed state keyed by group key, from a single read of each map.
+pub fn get_all_extension_group_states() -> HashMap<String, ExtensionGroupState> {
+    group_states(&get_extension_groups_map(), &get_extensions_map())
+}
+
+fn group_states(
+    groups: &HashMap<String, ExtensionGroup>,
+    extensions: &HashMap<String, ExtensionEntry>,
+) -> HashMap<String, ExtensionGroupState> {
+    groups
+        .iter()
+        .map(|(key, group)| (key.clone(), compute_group_state(group, extensions)))
+        .collect()
+}
+
+/// The keys of the groups whose computed state is `state`, sorted.
+pub fn get_groups_in_state(state: ExtensionGroupState) -> Vec<String> {
+    keys_in_state(get_all_extension_group_states(), state)
+}
+
+fn keys_in_state(
+    states: HashMap<String, ExtensionGroupState>,
+    state: ExtensionGroupState,
+) -> Vec<String> {
+    let mut keys: Vec<String> = states
+        .into_iter()
+        .filter(|(_, group_state)| *group_state == state)
+        .map(|(key, _)| key)
+        .collect();
+    keys.sort();
+    keys
+}
+
+/// What `set_extension_group_enabled` did to each member of the group.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct GroupApplyResult {
+    /// Members whose `enabled` flag was flipped.
+    pub changed: Vec<String>,
+    /// Members that were already in the requested state.
+    pub unchanged: Vec<String>,
+    /// Members that aren't in the extensions map.
+    pub missing: Vec<String>,
+    /// Members of a `Mask` group being enabled that stay off because their own `enabled`
+    /// flag is false.
+    pub masked_off: Vec<String>,
+}
+
+impl GroupApplyResult {
+    /// Whether every member is now in the requested state.
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty() && self.masked_off.is_empty()
+    }
+}
+
+/// Enables or disables every member of the group, reporting per member what happened. The
+/// config is only saved if at least one extension actually changed.
+pub fn set_extension_group_enabled(
+    name: &str,
+    enabled: bool,
+) -> Result<GroupApplyResult, ExtensionGroupError> {
+    apply_extension_group_enabled(name, enabled).map(|(result, _)| result)
+}
+
+/// Enables every member of the group. If the group is exclusive with other groups, their
+/// members (minus any shared with this group) are disabled first in the same save, and the
+/// keys of the groups that were switched off are returned.
+pub fn enable_extension_group(name: &str) -> Result<Vec<String>, ExtensionGroupError> {
+    apply_extension_group_enabled(name, true).map(|(_, disabled_groups)| disabled_groups)
+}
+
+pub fn disable_extension_group(name: &str) -> Result<(), ExtensionGroupError> {
+    set_extension_group_enabled(name, false).map(|_| ())
+}
+
+fn apply_extension_group_enabled(
+    name: &str,
+    enabled: bool,
+) -> Result<(GroupApplyResult, Vec<String>), ExtensionGroupError> {
+    let mut groups = get_extension_groups_map();
+    let mut extensions = get_extensions_layer(ExtensionLayer::Global);
+    let outcome = apply_group_enabled_to_maps(name, enabled, &mut groups, &mut extensions)?;
+
+    match (outcome.extensions_changed, outcome.groups_changed) {
+        (true, true) => save_extensions_and_groups(extensions, groups)?,
+        (true, false) => save_extensions_map(extensions)?,
+        (false, true) => save_extension_groups_map(groups)?,
+        (false, false) => {}
+    }
+    Ok((outcome.result, outcome.disabled_groups))
+}
+
+/// What enabling or disabling a group did to the in-memory maps, and which of them changed.
+struct GroupEnableOutcome {
+    result: GroupApplyResult,
+    disabled_groups: Vec<String>,
+    extensions_changed: bool,
+    groups_changed: bool,
+}
+
+fn apply_group_enabled_to_maps(
+    name: &str,
+    enabled: bool,
+    groups: &mut HashMap<String, ExtensionGroup>,
+    extensions: &mut HashMap<String, ExtensionEntry>,
+) -> Result<GroupEnableOutcome, ExtensionGroupError> {
+    let group_key = name_to_key(name);
+    let group = groups
+        .get(&group_key)
+        .cloned()
+        .ok_or_else(|| ExtensionGroupError::NotFound(name.to_string()))?;
+
+    let disabled_groups = if enabled {
+        disable_conflicting_groups(&group, groups, extensions)
+    } else {
+        Vec::new()
+    };
+    let members = group.resolve_members(extensions);
+    let result = match group.toggle_mode {
+        GroupToggleMode::MutateMembers => apply_members_enabled(&members, extensions, enabled),
+        GroupToggleMode::Mask => {
+            apply_group_mask(&group_key, &members, groups, extensions, enabled)
+        }
+    };
+
+    let (masked_conflicts, mutated_conflicts): (Vec<&String>, Vec<&String>) = disabled_groups
+        .iter()
+        .partition(|key| groups[key.as_str()].toggle_mode == GroupToggleMode::Mask);
+    let (extensions_changed, groups_changed) = match group.toggle_mode {
+        GroupToggleMode::MutateMembers => (
+            !result.changed.is_empty() || !mutated_conflicts.is_empty(),
+            !masked_conflicts.is_empty(),
+        ),
+        GroupToggleMode::Mask => (
+            !mutated_conflicts.is_empty(),
+            group.enabled != enabled || !masked_conflicts.is_empty(),
+        ),
+    };
+
+    Ok(GroupEnableOutcome {
+        result,
+        disabled_groups,
+        extensions_changed,
+        groups_changed,
+    })
+}
+
+/// One step of a `simulate_group_operations` sequence.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(tag = "op", content = "name", rename_all = "snake_case")]
+pub enum GroupOp {
+    Enable(String),
+    Disable(String),
+}
+
+/// Previews a sequence of group enables and disables without writing anything: the
+/// operations are applied in order, with the same member resolution, exclusivity and mask
+/// handling as `enable_extension_group` and `disable_extension_group`, to a copy of the
+/// stored maps. Returns each extension's resulting effective enabled state. An operation
+/// naming an unknown group is skipped, as the real call would fail without changing anything.
+pub fn simulate_group_operations(ops: &[GroupOp]) -> HashMap<String, bool> {
+    simulate_group_ops(
+        ops,
+        get_extension_groups_map(),
+        get_extensions_layer(ExtensionLayer::Global),
+    )
+}
+
+fn simulate_group_ops(
+    ops: &[GroupOp],
+    mut groups: HashMap<String, ExtensionGroup>,
+    mut extensions: HashMap<String, ExtensionEntry>,
+) -> HashMap<String, bool> {
+    for op in ops {
+        let (name, enabled) = match op {
+            GroupOp::Enable(name) => (name, true),
+            GroupOp::Disable(name) => (name, false),
+        };
+        if let Err(e) = apply_group_enabled_to_maps(name, enabled, &mut groups, &mut extensions) {
+            tracing::debug!("Skipping simulated group operation: {}", e);
+        }
+    }
+    extensions
+        .keys()
+        .map(|key| {
+            (
+                key.clone(),
+                is_effectively_enabled(key, &extensions, &groups),
+            )
+        })
+        .collect()
+}
+
+/// Flips a `Mask` group's own flag and reports which members' effective state changed.
+fn apply_group_mask(
+    group_key: &str,
+    members: &[String],
+    groups: &mut HashMap<String, ExtensionGroup>,
+    extensions: &HashMap<String, ExtensionEntry>,
+    enabled: bool,
+) -> GroupApplyResult {
+    let before: Vec<bool> = members
+        .iter()
+        .map(|key| is_effectively_enabled(key, extensions, groups))
+        .collect();
+    if let Some(group) = groups.get_mut(group_key) {
+        group.enabled = enabled;
+    }
+
+    let mut result = GroupApplyResult::default();
+    for (key, was_enabled) in members.iter().zip(before) {
+        if !extensions.contains_key(key) {
+            result.missing.push(key.clone());
+        } else if enabled && !extensions[key].enabled {
+            result.masked_off.push(key.clone());
+        } else if is_effectively_enabled(key, extensions, groups) != was_enabled {
+            result.changed.push(key.clone());
+        } else {
+            result.unchanged.push(key.clone());
+        }
+    }
+    result
+}
+
+fn apply_members_enabled(
+    keys: &[String],
+    extensions: &mut HashMap<String, ExtensionEntry>,
+    enabled: bool,
+) -> GroupApplyResult {
+    let mut result = GroupApplyResult::default();
+    let now = now_unix();
+    for key in keys {
+        match extensions.get_mut(key) {
+            // A placeholder can't run, so it counts as missing rather than being enabled.
+            Some(entry) if is_placeholder(entry) => result.missing.push(key.clone()),
+            Some(entry) if entry.enabled != enabled => {
+                entry.enabled = enabled;
+                entry.updated_at = Some(now);
+                result.changed.push(key.clone());
+            }
+            Some(_) => result.unchanged.push(key.clone()),
+            None => result.missing.push(key.clone()),
+        }
+    }
+    result
+}
+
+fn set_members_enabled(
+    keys: &[String],
+    extensions: &mut HashMap<String, ExtensionEntry>,
+    enabled: bool,
+) -> bool {
+    !apply_members_enabled(keys, extensions, enabled)
+        .changed
+        .is_empty()
+}
+
+fn disable_conflicting_groups(
+    group: &ExtensionGroup,
+    groups: &mut HashMap<String, ExtensionGroup>,
+    extensions: &mut HashMap<String, ExtensionEntry>,
+) -> Vec<String> {
+    let own_members = group.resolve_members(extensions);
+    let mut disabled_groups = Vec::new();
+    for conflict_key in &group.exclusive_with {
+        let Some(conflict) = groups.get_mut(conflict_key) else {
+            continue;
+        };
+        if conflict.toggle_mode == GroupToggleMode::Mask {
+            if conflict.enabled {
+                conflict.enabled = false;
+                disabled_groups.push(conflict_key.clone());
+            }
+            continue;
+        }
+        let keys: Vec<String> = conflict
+            .resolve_members(extensions)
+            .into_iter()
+            .filter(|key| !own_members.contains(key))
+            .collect();
+        if set_members_enabled(&keys, extensions, false) {
+            disabled_groups.push(conflict_key.clone());
+        }
+    }
+    disabled_groups
+}
+
+/// Flips a group between fully enabled and fully disabled. A `Mixed` group always
+/// resolves toward `Enabled` first so repeated clicks behave predictably.
+pub fn toggle_extension_group(name: &str) -> Result<ExtensionGroupState, ExtensionGroupError> {
+    let group = get_extension_group_by_name(name)
+        .ok_or_else(|| ExtensionGroupError::NotFound(name.to_string()))?;
+    let state = if group.toggle_mode == GroupToggleMode::Mask {
+        // A mask group follows its own switch; its members may legitimately stay mixed.
+        if group.enabled {
+            ExtensionGroupState::Enabled
+        } else {
+            ExtensionGroupState::Disabled
+        }
+    } else {
+        compute_group_state(&group, &get_extensions_map())
+    };
+
+    match state {
+        ExtensionGroupState::Enabled => disable_extension_group(name)?,
+        ExtensionGroupState::Disabled | ExtensionGroupState::Mixed => {
+            enable_extension_group(name)?;
+        }
+    }
+
+    get_extension_group_state(name).ok_or_else(|| ExtensionGroupError::NotFound(name.to_string()))
+}
+
+pub async fn get_extension_groups_map_async() -> Result<HashMap<String, ExtensionGroup>, JoinError>
+{
+    tokio::task::spawn_blocking(get_extension_groups_map).await
+}
+
+pub async fn save_extension_groups_map_async(
+    groups: HashMap<String, ExtensionGroup>,
+) -> Result<Result<(), SaveError>, JoinError> {
+    tokio::task::spawn_blocking(move || save_extension_groups_map(groups)).await
+}
+
+pub async fn get_extension_group_state_async(
+    name: &str,
+) -> Result<Option<ExtensionGroupState>, JoinError> {
+    let name = name.to_string();
+    tokio::task::spawn_blocking(move || get_extension_group_state(&name)).await
+}
+
+pub const REDACTED_PLACEHOLDER: &str = "<redacted>";
+const SECRET_NAME_MARKERS: [&str; 4] = ["TOKEN", "KEY", "SECRET", "PASSWORD"];
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum SerializeError {
+    #[error("failed to serialize to TOML: {0}")]
+    Serialize(String),
+    #[error("failed to parse TOML: {0}")]
+    Deserialize(String),
+}
+
+/// The effective extensions as a TOML document with one table per key, sorted by key.
+pub fn extensions_to_toml() -> Result<String, SerializeError> {
+    map_to_toml(&get_extensions_map())
+}
+
+/// The extension groups as a TOML document with one table per key, sorted by key.
+pub fn extension_groups_to_toml() -> Result<String, SerializeError> {
+    map_to_toml(&get_extension_groups_map())
+}
+
+/// Parses a document written by `extensions_to_toml`. Nothing is stored; pass the result to
+/// e.g. `replace_all_extensions` to import it.
+pub fn extensions_from_toml(
+    content: &str,
+) -> Result<HashMap<String, ExtensionEntry>, SerializeError> {
+    let value: toml::Value =
+        toml::from_str(content).map_err(|e| SerializeError::Deserialize(e.to_string()))?;
+    let value =
+        serde_json::to_value(value).map_err(|e| SerializeError::Deserialize(e.to_string()))?;
+    serde_json::from_value(value).map_err(|e| SerializeError::Deserialize(e.to_string()))
+}
+
+/// Goes through JSON first so `#[serde(flatten)]` fields come out as plain keys of each entry's
+/// table, and drops nulls, which TOML has no way to represent.
+fn map_to_toml<T: Serialize>(map: &HashMap<String, T>) -> Result<String, SerializeError> {
+    let mut sorted = serde_json::Map::new();
+    let mut keys: Vec<&String> = map.keys().collect();
+    keys.sort();
+    for key in keys {
+        let value = serde_json::to_value(&map[key])
+            .map_err(|e| SerializeError::Serialize(e.to_string()))?;
+        if let Some(value) = strip_nulls(value) {
+            sorted.insert(key.clone(), value);
+        }
+    }
+    toml::to_string(&Value::Object(sorted)).map_err(|e| SerializeError::Serialize(e.to_string()))
+}
+
+fn strip_nulls(value: Value) -> Option<Value> {
+    match value {
+        Value::Null => None,
+        Value::Object(obj) => Some(Value::Object(
+            obj.into_iter()
+                .filter_map(|(k, v)| strip_nulls(v).map(|v| (k, v)))
+                .collect(),
+        )),
+        Value::Array(items) => Some(Value::Array(
+            items.into_iter().filter_map(strip_nulls).collect(),
+        )),
+        other => Some(other),
+    }
+}
+
+/// A shareable snapshot of the extensions and groups maps.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct ExtensionConfigBundle {
+    pub extensions: HashMap<String, ExtensionEntry>,
+    #[serde(default)]
+    pub groups: HashMap<String, ExtensionGroup>,
+}
+
+pub fn export_config_bundle(redact_secrets: bool) -> ExtensionConfigBundle {
+    let mut extensions = get_extensions_layer(ExtensionLayer::Global);
+    if redact_secrets {
+        for entry in extensions.values_mut() {
+            entry.config = redact_extension_secrets(&entry.config);
+        }
+    }
+    ExtensionConfigBundle {
+        extensions,
+        groups: get_extension_groups_map(),
+    }
+}
+
+/// Validates every entry of the bundle and merges it into the stored config, returning the
+/// imported extension keys. Nothing is written if any entry is invalid or still carries a
+/// redacted placeholder instead of a real secret; otherwise the extensions and groups are
+/// written together as a single undoable change.
+pub fn import_config_bundle(
+    bundle: ExtensionConfigBundle,
+    mode: MergeMode,
+) -> Result<Vec<String>, ExtensionError> {
+    for (key, entry) in &bundle.extensions {
+        validate_extension_config(&entry.config)?;
+        if let Some(name) = redacted_secret_name(&entry.config) {
+            return Err(ExtensionError::RedactedSecret {
+                key: key.clone(),
+                name,
+            });
+        }
+    }
+
+    let mut keys: Vec<String> = bundle.extensions.keys().cloned().collect();
+    keys.sort();
+
+    let extensions = merge_extension_maps(
+        get_extensions_layer(ExtensionLayer::Global),
+        bundle.extensions,
+        mode,
+    );
+    if bundle.groups.is_empty() {
+        save_extensions_map(extensions)?;
+        return Ok(keys);
+    }
+
+    let mut groups = get_extension_groups_map();
+    for (key, mut group) in bundle.groups {
+        normalize_group_members(&mut group);
+        groups.insert(key, group);
+    }
+    save_extensions_and_groups(extensions, groups)?;
+    Ok(keys)
+}
+
+/// One server entry of an MCP registry manifest. Transport may be given as a string or as an
+/// object with a `type` (and optionally `url`) field; when absent it is inferred from whether
+/// the server has a `command` or a `url`.
+#[derive(Debug, Deserialize)]
+struct McpManifestServer {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    command: Option<String>,
+    #[serde(default)]
+    args: Vec<String>,
+    #[serde(default)]
+    env: HashMap<String, String>,
+    #[serde(default)]
+    transport: Option<Value>,
+    #[serde(default)]
+    url: Option<String>,
+    #[serde(default)]
+    headers: HashMap<String, String>,
+}
+
+/// Parses an MCP registry manifest and adds every server it can map to the stored config,
+/// each as an extension with the given `enabled` flag. Servers that can't be mapped, fail
+/// validation or collide with an existing key are skipped with a warning. Returns the keys
+/// added, in manifest order.
+pub fn import_from_mcp_manifest(manifest: &str, enable: bool) -> Result<Vec<String>, ImportError> {
+    let (entries, mut skipped) =
+        parse_mcp_manifest(manifest, enable, &get_default_extension_description())?;
+
+    let mut extensions = get_extensions_layer(ExtensionLayer::Global);
+    let mut added = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let key = entry.config.key();
+        if extensions.contains_key(&key) {
+            skipped.push(format!("{key}: an extension with this key already exists"));
+            continue;
+        }
+        extensions.insert(key.clone(), entry);
+        added.push(key);
+    }
+
+    for reason in &skipped {
+        warn!("Skipping MCP manifest server {reason}");
+    }
+    if !added.is_empty() {
+        save_extensions_map(extensions)?;
+    }
+    Ok(added)
+}
+
+/// Maps a manifest to extension entries, returning them alongside the reasons any servers
+/// were skipped. Accepts `{"mcpServers": {name: server}}`, `{"servers": ...}` with either a
+/// map or a list, or a bare list of servers.
+fn parse_mcp_manifest(
+    manifest: &str,
+    enable: bool,
+    default_description: &str,
+) -> Result<(Vec<ExtensionEntry>, Vec<String>), ImportError> {
+    let raw: Value =
+        serde_json::from_str(manifest).map_err(|e| ImportError::InvalidManifest(e.to_string()))?;
+    let servers = match raw {
+        Value::Object(mut obj) => {
+            match obj.remove("mcpServers").or_else(|| obj.remove("servers")) {
+                Some(servers) => servers,
+                None => {
+                    return Err(ImportError::InvalidManifest(
+                        "expected a \"mcpServers\" or \"servers\" field".to_string(),
+                    ))
+                }
+            }
+        }
+        other => other,
+    };
+    let servers: Vec<(Option<String>, Value)> = match servers {
+        Value::Object(obj) => obj.into_iter().map(|(k, v)| (Some(k), v)).collect(),
+        Value::Array(items) => items.into_iter().map(|v| (None, v)).collect(),
+        other => {
+            return Err(ImportError::InvalidManifest(format!(
+                "expected a map or list of servers, got {other}"
+            )))
+        }
+    };
+
+    let mut entries = Vec::with_capacity(servers.len());
+    let mut seen = HashSet::new();
+    let mut skipped = Vec::new();
+    for (index, (map_key, value)) in servers.into_iter().enumerate() {
+        let label = map_key.clone().unwrap_or_else(|| format!("#{index}"));
+        let server: McpManifestServer = match serde_json::from_value(value) {
+            Ok(server) => server,
+            Err(e) => {
+                skipped.push(format!("{label}: {e}"));
+                continue;
+            }
+        };
+        let config = match mcp_server_config(server, map_key, default_description) {
+            Ok(config) => config,
+            Err(reason) => {
+                skipped.push(format!("{label}: {reason}"));
+                continue;
+            }
+        };
+        if let Err(e) = validate_extension_config(&config) {
+            skipped.push(format!("{label}: {e}"));
+            continue;
+        }
+        if !seen.insert(config.key()) {
+            skipped.push(format!("{label}: duplicate of an earlier server"));
+            continue;
+        }
+        entries.push(ExtensionEntry {
+            enabled: enable,
+            load_order: None,
+            updated_at: Some(now_unix()),
+            disabled_tools: Vec::new(),
+            config,
+        });
+    }
+    Ok((entries, skipped))
+}
+
+fn mcp_server_config(
+    server: McpManifestServer,
+    map_key: Option<String>,
+    default_description: &str,
+) -> Result<ExtensionConfig, String> {
+    let name = match server.name.or(map_key) {
+        Some(name) if !name.trim().is_empty() => name,
+        _ => return Err("server has no name".to_string()),
+    };
+    let description = server
+        .description
+        .unwrap_or_else(|| default_description.to_string());
+
+    let (transport, transport_url) = match server.transport {
+        Some(Value::String(transport)) => (Some(transport), None),
+        Some(Value::Object(obj)) => (
+            obj.get("type").and_then(Value::as_str).map(str::to_string),
+            obj.get("url").and_then(Value::as_str).map(str::to_string),
+        ),
+        Some(Value::Null) | None => (None, None),
+        Some(other) => return Err(format!("unsupported transport {other}")),
+    };
+    let url = server.url.or(transport_url);
+    let transport = match transport {
+        Some(transport) => transport.to_lowercase().replace('-', "_"),
+        None if server.command.is_some() => "stdio".to_string(),
+        None if url.is_some() => "streamable_http".to_string(),
+        None => return Err("server has neither a command nor a url".to_string()),
+    };
+
+    let envs = Envs::new(server.env);
+    match transport.as_str() {
+        "stdio" => {
+            let cmd = server
+                .command
+                .ok_or_else(|| "stdio server has no command".to_string())?;
+            Ok(ExtensionConfig::Stdio {
+                name,
+                description,
+                cmd,
+                args: server.args,
+                envs,
+                env_keys: Vec::new(),
+                timeout: Some(DEFAULT_EXTENSION_TIMEOUT),
+                bundled: None,
+                available_tools: Vec::new(),
+            })
+        }
+        "sse" => Ok(ExtensionConfig::Sse {
+            name,
+            description,
+            uri: url.ok_or_else(|| "sse server has no url".to_string())?,
+            envs,
+            env_keys: Vec::new(),
+            timeout: Some(DEFAULT_EXTENSION_TIMEOUT),
+            bundled: None,
+            available_tools: Vec::new(),
+        }),
+        "streamable_http" | "http" => Ok(ExtensionConfig::StreamableHttp {
+            name,
+            description,
+            uri: url.ok_or_else(|| "http server has no url".to_string())?,
+            envs,
+            env_keys: Vec::new(),
+            headers: server.headers,
+            timeout: Some(DEFAULT_EXTENSION_TIMEOUT),
+            bundled: None,
+            available_tools: Vec::new(),
+        }),
+        other => Err(format!("unsupported transport {other:?}")),
+    }
+}
+
+fn is_secret_name(name: &str) -> bool {
+    let upper = name.to_uppercase();
+    SECRET_NAME_MARKERS
+        .iter()
+        .any(|marker| upper.contains(marker))
+}
+
+/// Returns a copy of the config with the values of secret-looking env vars and headers
+/// (names containing TOKEN, KEY, SECRET or PASSWORD) replaced by `REDACTED_PLACEHOLDER`.
+pub fn redact_extension_secrets(config: &ExtensionConfig) -> ExtensionConfig {
+    let redact = |values: HashMap<String, String>| -> HashMap<String, String> {
+        values
+            .into_iter()
+            .map(|(name, value)| {
+                if is_secret_name(&name) {
+                    (name, REDACTED_PLACEHOLDER.to_string())
+                } else {
+                    (name, value)
+                }
+            })
+            .collect()
+    };
+
+    let mut redacted = config.clone();
+    match &mut redacted {
+        ExtensionConfig::Sse { envs, .. } | ExtensionConfig::Stdio { envs, .. } => {
+            *envs = Envs::new(redact(envs.get_env()));
+        }
+        ExtensionConfig::StreamableHttp { envs, headers, .. } => {
+            *envs = Envs::new(redact(envs.get_env()));
+            *headers = redact(std::mem::take(headers));
+        }
+        _ => {}
+    }
+    redacted
+}
+
+fn redacted_secret_name(config: &ExtensionConfig) -> Option<String> {
+    let find = |values: &HashMap<String, String>| {
+        values
+            .iter()
+            .find(|(_, value)| value.as_str() == REDACTED_PLACEHOLDER)
+            .map(|(name, _)| name.clone())
+    };
+    match config {
+        ExtensionConfig::Sse { envs, .. } | ExtensionConfig::Stdio { envs, .. } => {
+            find(&envs.get_env())
+        }
+        ExtensionConfig::StreamableHttp { envs, headers, .. } => {
+            find(&envs.get_env()).or_else(|| find(headers))
+        }
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExtensionProbeStatus {
+    Ok,
+    CommandNotFound,
+    Unreachable,
+    Skipped,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ExtensionProbeResult {
+    pub key: String,
+    pub status: ExtensionProbeStatus,
+    pub detail: String,
+}
+
+/// Bounds a remote probe as a whole: resolving the host plus connecting to what it resolves to.
+const PROBE_URI_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Cheap preflight check of every enabled extension: stdio commands must resolve to an
+/// executable, remote endpoints must accept a TCP connection. In-process extensions are
+/// reported as `Skipped`. Results are sorted by key.
+pub fn probe_extensions() -> Vec<ExtensionProbeResult> {
+    let mut results: Vec<ExtensionProbeResult> = get_extensions_map()
+        .into_iter()
+        .filter(|(_, entry)| entry.enabled)
+        .map(|(key, entry)| {
+            let (status, detail) = probe_extension(&entry.config);
+            ExtensionProbeResult {
+                key,
+                status,
+                detail,
+            }
+        })
+        .collect();
+    results.sort_by(|a, b| a.key.cmp(&b.key));
+    results
+}
+
+fn probe_extension(config: &ExtensionConfig) -> (ExtensionProbeStatus, String) {
+    match config {
+        ExtensionConfig::Stdio { cmd, .. } => probe_command(cmd),
+        ExtensionConfig::InlinePython { .. } => probe_command("uvx"),
+        ExtensionConfig::Sse { uri, .. } | ExtensionConfig::StreamableHttp { uri, .. } => {
+            probe_uri(uri)
+        }
+        ExtensionConfig::Builtin { .. }
+        | ExtensionConfig::Platform { .. }
+        | ExtensionConfig::Frontend { .. } => (
+            ExtensionProbeStatus::Skipped,
+            "runs inside goose".to_string(),
+        ),
+        ExtensionConfig::Unresolved { .. } => (
+            ExtensionProbeStatus::Skipped,
+            "placeholder; not configured yet".to_string(),
+        ),
+    }
+}
+
+fn probe_command(cmd: &str) -> (ExtensionProbeStatus, String) {
+    match resolve_command(cmd) {
+        Some(path) => (ExtensionProbeStatus::Ok, path.display().to_string()),
+        None => (
+            ExtensionProbeStatus::CommandNotFound,
+            format!("{cmd} was not found or is not executable"),
+        ),
+    }
+}
+
+fn resolve_command(cmd: &str) -> Option<PathBuf> {
+    let cmd_path = Path::new(cmd);
+    if cmd_path.components().count() > 1 || cmd_path.is_absolute() {
+        return is_executable(cmd_path).then(|| cmd_path.to_path_buf());
+    }
+
+    let extensions: Vec<String> = if cfg!(windows) {
+        std::env::var("PATHEXT")
+            .unwrap_or_else(|_| ".EXE;.CMD;.BAT".to_string())
+            .split(';')
+            .map(|ext| ext.to_string())
+            .chain(std::iter::once(String::new()))
+            .collect()
+    } else {
+        vec![String::new()]
+    };
+
+    let path_var = std::env::var_os("PATH")?;
+    std::env::split_paths(&path_var).find_map(|dir| {
+        extensions.iter().find_map(|ext| {
+            let candidate = dir.join(format!("{cmd}{ext}"));
+            is_executable(&candidate).then_some(candidate)
+        })
+    })
+}
+
+fn is_executable(path: &Path) -> bool {
+    let Ok(metadata) = std::fs::metadata(path) else {
+        return false;
+    };
+    if !metadata.is_file() {
+        return false;
+    }
+    #[cfg(unix)]
+    let executable = {
+        use std::os::unix::fs::PermissionsExt;
+        metadata.permissions().mode() & 0o111 != 0
+    };
+    #[cfg(not(unix))]
+    let executable = true;
+    executable
+}
+
+fn probe_uri(uri: &str) -> (ExtensionProbeStatus, String) {
+    probe_uri_within(uri, PROBE_URI_TIMEOUT)
+}
+
+fn probe_uri_within(uri: &str, timeout: std::time::Duration) -> (ExtensionProbeStatus, String) {
+    use std::net::TcpStream;
+
+    let deadline = Instant::now() + timeout;
+    let url = match url::Url::parse(uri) {
+        Ok(url) => url,
+        Err(e) => {
+            return (
+                ExtensionProbeStatus::Unreachable,
+                format!("invalid uri: {e}"),
+            )
+        }
+    };
+    let (Some(host), Some(port)) = (url.host_str(), url.port_or_known_default()) else {
+        return (
+            ExtensionProbeStatus::Unreachable,
+            format!("{uri} has no host or port"),
+        );
+    };
+
+    let addrs = match resolve_within(host, port, timeout) {
+        Ok(addrs) => addrs,
+        Err(detail) => return (ExtensionProbeStatus::Unreachable, detail),
+    };
+    let mut last_error = format!("{host} did not resolve to any address");
+    for addr in addrs {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return (
+                ExtensionProbeStatus::Unreachable,
+                format!("timed out connecting to {host} after {timeout:?}"),
+            );
+        }
+        match TcpStream::connect_timeout(&addr, remaining) {
+            Ok(_) => return (ExtensionProbeStatus::Ok, format!("connected to {addr}")),
+            Err(e) => last_error = format!("{addr}: {e}"),
+        }
+    }
+    (ExtensionProbeStatus::Unreachable, last_error)
+}
+
+/// `to_socket_addrs` blocks for as long as the system resolver takes, so the lookup runs on
+/// its own thread. One still running at the deadline is left to finish in the background.
+fn resolve_within(
+    host: &str,
+    port: u16,
+    timeout: std::time::Duration,
+) -> Result<Vec<std::net::SocketAddr>, String> {
+    use std::net::ToSocketAddrs;
+    use std::sync::mpsc::{self, RecvTimeoutError};
+
+    let (tx, rx) = mpsc::channel();
+    let lookup = host.to_string();
+    std::thread::spawn(move || {
+        let _ = tx.send(
+            (lookup.as_str(), port)
+                .to_socket_addrs()
+                .map(Vec::from_iter),
+        );
+    });
+    match rx.recv_timeout(timeout) {
+        Ok(Ok(addrs)) => Ok(addrs),
+        Ok(Err(e)) => Err(format!("failed to resolve {host}: {e}")),
+        Err(RecvTimeoutError::Timeout) => {
+            Err(format!("timed out resolving {host} after {timeout:?}"))
+        }
+        Err(RecvTimeoutError::Disconnected) => Err(format!("failed to resolve {host}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, enabled: bool) -> ExtensionEntry {
+        entry_with(
+            ExtensionConfig::stdio(name, "cmd", "", DEFAULT_EXTENSION_TIMEOUT),
+            enabled,
+        )
+    }
+
+    fn entry_with(config: ExtensionConfig, enabled: bool) -> ExtensionEntry {
+        ExtensionEntry {
+            enabled,
+            load_order: None,
+            updated_at: None,
+            disabled_tools: Vec::new(),
+            config,
+        }
+    }
+
+    fn extensions(entries: &[(&str, bool)]) -> HashMap<String, ExtensionEntry> {
+        entries
+            .iter()
+            .map(|(name, enabled)| (name_to_key(name), entry(name, *enabled)))
+            .collect()
+    }
+
+    fn group(name: &str, keys: &[&str]) -> ExtensionGroup {
+        ExtensionGroup::new(name, keys.iter().map(|k| k.to_string()).collect())
+    }
+
+    #[test]
+    fn test_group_state() {
+        let exts = extensions(&[("a", true), ("b", true), ("c", false)]);
+
+        assert_eq!(
+            compute_group_state(&group("g", &["a", "b"]), &exts),
+            ExtensionGroupState::Enabled
+        );
+        assert_eq!(
+            compute_group_state(&group("g", &["c"]), &exts),
+            ExtensionGroupState::Disabled
+        );
+        assert_eq!(
+            compute_group_state(&group("g", &["a", "c"]), &exts),
+            ExtensionGroupState::Mixed
+        );
+        assert_eq!(
+            compute_group_state(&group("g", &["a", "missing"]), &exts),
+            ExtensionGroupState::Mixed
+        );
+        assert_eq!(
+            compute_group_state(&group("g", &[]), &exts),
+            ExtensionGroupState::Disabled
+        );
+    }
+
+    #[test]
+    fn test_missing_group_members() {
+        let exts = extensions(&[("a", true), ("b", false)]);
+
+        assert!(missing_group_members(&group("g", &["a", "b"]), &exts).is_empty());
+        assert_eq!(
+            missing_group_members(&group("g", &["a", "typo", "b", "later"]), &exts),
+            vec!["typo".to_string(), "later".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_disable_conflicting_groups() {
+        let mut exts = extensions(&[("bash", true), ("zsh", true), ("shared", true)]);
+        let zsh = group("zsh", &["zsh", "shared"]);
+        let bash = group("bash", &["bash", "shared"]).with_exclusive_with(vec!["zsh".into()]);
+        let mut groups: HashMap<String, ExtensionGroup> =
+            [(zsh.key(), zsh.clone()), (bash.key(), bash.clone())].into();
+
+        let disabled = disable_conflicting_groups(&bash, &mut groups, &mut exts);
+
+        assert_eq!(disabled, vec!["zsh".to_string()]);
+        assert!(!exts["zsh"].enabled);
+        assert!(exts["shared"].enabled);
+        assert!(exts["bash"].enabled);
+
+        // Nothing left to switch off, so the conflict is no longer reported.
+        assert!(disable_conflicting_groups(&bash, &mut groups, &mut exts).is_empty());
+    }
+
+    #[test]
+    fn test_apply_exclusive_enabled() {
+        let mut exts = extensions(&[("a", false), ("b", true)]);
+        exts.insert(
+            "todo".to_string(),
+            entry_with(
+                ExtensionConfig::Platform {
+                    name: "todo".to_string(),
+                    description: String::new(),
+                    bundled: Some(true),
+                    available_tools: Vec::new(),
+                },
+                true,
+            ),
+        );
+
+        set_test_now(Some(100));
+        apply_exclusive_enabled(&["a".to_string()], &mut exts, false);
+        set_test_now(None);
+        assert!(exts["a"].enabled);
+        assert!(!exts["b"].enabled);
+        assert!(exts["todo"].enabled);
+        assert_eq!(exts["a"].updated_at, Some(100));
+        assert_eq!(exts["b"].updated_at, Some(100));
+        assert_eq!(exts["todo"].updated_at, None);
+
+        apply_exclusive_enabled(&["a".to_string()], &mut exts, true);
+        assert!(!exts["todo"].enabled);
+    }
+
+    #[test]
+    fn test_read_project_extensions() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".goose")).unwrap();
+        std::fs::write(
+            dir.path().join(PROJECT_EXTENSIONS_FILE),
+            "repo-tool:\n  enabled: true\n  type: stdio\n  name: repo-tool\n  cmd: ./tool\n  args: []\n  timeout: 300\n",
+        )
+        .unwrap();
+
+        // Read the file directly: the overlay itself is process-wide state and tests run in
+        // parallel.
+        let path = dir.path().join(PROJECT_EXTENSIONS_FILE);
+        let project = read_extensions_file(&path, DEFAULT_EXTENSION_DESCRIPTION);
+        assert_eq!(project.len(), 1);
+        assert!(project["repo-tool"].enabled);
+        assert_eq!(project["repo-tool"].config.name(), "repo-tool");
+    }
+
+    fn streamable_http(uri: &str, headers: &[(&str, &str)]) -> ExtensionConfig {
+        ExtensionConfig::StreamableHttp {
+            name: "gateway".to_string(),
+            description: String::new(),
+            uri: uri.to_string(),
+            envs: Default::default(),
+            env_keys: Vec::new(),
+            headers: headers
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            timeout: Some(60),
+            bundled: None,
+            available_tools: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_streamable_http_entry_round_trip() {
+        let entry = entry_with(
+            streamable_http("https://mcp.internal/mcp", &[("X-Auth", "token")]),
+            true,
+        );
+
+        let value = serde_json::to_value(&entry).unwrap();
+        assert_eq!(value["type"], "streamable_http");
+        assert_eq!(value["headers"]["X-Auth"], "token");
+
+        let parsed: ExtensionEntry = serde_json::from_value(value).unwrap();
+        match parsed.config {
+            ExtensionConfig::StreamableHttp {
+                uri,
+                headers,
+                timeout,
+                ..
+            } => {
+                assert_eq!(uri, "https://mcp.internal/mcp");
+                assert_eq!(headers["X-Auth"], "token");
+                assert_eq!(timeout, Some(60));
+            }
+            other => panic!("unexpected variant: {other}"),
+        }
+    }
+
+    #[test]
+    fn test_validate_remote_config() {
+        assert!(validate_extension_config(&streamable_http(
+            "https://mcp.internal/mcp",
+            &[("X-Auth", "token")]
+        ))
+        .is_ok());
+        assert!(matches!(
+            validate_extension_config(&streamable_http("not a uri", &[])),
+            Err(ExtensionValidationError::InvalidUri { .. })
+        ));
+        assert_eq!(
+            validate_extension_config(&streamable_http(
+                "https://mcp.internal/mcp",
+                &[("X-Auth", " ")]
+            )),
+            Err(ExtensionValidationError::EmptyHeader("X-Auth".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_key_order() {
+        let a: Value =
+            serde_json::from_str(r#"{"x": {"enabled": true, "name": "x"}, "y": {}}"#).unwrap();
+        let b: Value =
+            serde_json::from_str(r#"{"y": {}, "x": {"name": "x", "enabled": true}}"#).unwrap();
+        let c: Value =
+            serde_json::from_str(r#"{"x": {"enabled": false, "name": "x"}, "y": {}}"#).unwrap();
+
+        assert_eq!(
+            fingerprint_values(&a, &Value::Null),
+            fingerprint_values(&b, &Value::Null)
+        );
+        assert_ne!(
+            fingerprint_values(&a, &Value::Null),
+            fingerprint_values(&c, &Value::Null)
+        );
+        assert_ne!(
+            fingerprint_values(&a, &Value::Null),
+            fingerprint_values(&Value::Null, &a)
+        );
+    }
+
+    #[test]
+    fn test_merge_json_patch() {
+        let mut target = serde_json::json!({
+            "enabled": true,
+            "type": "stdio",
+            "timeout": 300,
+            "envs": {"A": "1", "B": "2"}
+        });
+        merge_json_patch(
+            &mut target,
+            &serde_json::json!({"timeout": 600, "envs": {"B": null, "C": "3"}}),
+        );
+
+        assert_eq!(
+            target,
+            serde_json::json!({
+                "enabled": true,
+                "type": "stdio",
+                "timeout": 600,
+                "envs": {"A": "1", "C": "3"}
+            })
+        );
+    }
+
+    #[test]
+    fn test_sort_by_load_order() {
+        let with_order = |name: &str, order: Option<i32>| {
+            let mut e = entry(name, true);
+            e.load_order = order;
+            (name.to_string(), e)
+        };
+        let mut entries = vec![
+            with_order("zeta", None),
+            with_order("late", Some(10)),
+            with_order("alpha", None),
+            with_order("first", Some(-1)),
+            with_order("also-late", Some(10)),
+        ];
+
+        sort_by_load_order(&mut entries);
+
+        let keys: Vec<&str> = entries.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, vec!["first", "also-late", "late", "alpha", "zeta"]);
+    }
+
+    #[test]
+    fn test_load_order_defaults_to_none() {
+        let parsed: ExtensionEntry = serde_json::from_value(serde_json::json!({
+            "enabled": true,
+            "type": "builtin",
+            "name": "developer",
+            "description": "",
+            "display_name": null,
+            "timeout": 300
+        }))
+        .unwrap();
+        assert_eq!(parsed.load_order, None);
+    }
+
+    #[test]
+    fn test_validate_timeout_bounds() {
+        let with_timeout = |t: u64| ExtensionConfig::stdio("s", "cmd", "", t);
+
+        assert!(validate_extension_config(&with_timeout(DEFAULT_EXTENSION_TIMEOUT)).is_ok());
+        assert!(validate_extension_config(&with_timeout(MAX_EXTENSION_TIMEOUT)).is_ok());
+        assert_eq!(
+            validate_extension_config(&with_timeout(0)),
+            Err(ExtensionValidationError::InvalidTimeout(0))
+        );
+        assert_eq!(
+            validate_extension_config(&with_timeout(MAX_EXTENSION_TIMEOUT + 1)),
+            Err(ExtensionValidationError::InvalidTimeout(
+                MAX_EXTENSION_TIMEOUT + 1
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_extensions_strict() {
+        let valid = serde_json::json!({
+            "dev": {"enabled": true, "type": "builtin", "name": "dev", "display_name": null, "timeout": 300}
+        });
+        assert_eq!(
+            parse_extensions_strict(valid, DEFAULT_EXTENSION_DESCRIPTION)
+                .unwrap()
+                .len(),
+            1
+        );
+
+        let typo = serde_json::json!({
+            "dev": {"enabled": true, "type": "builtin", "name": "dev", "display_name": null, "timout": 30},
+            "other": {"enabled": true, "type": "stdio", "name": "other", "cmd": "x", "args": [], "timeout": 1, "arg": ["y"]}
+        });
+        assert_eq!(
+            parse_extensions_strict(typo, DEFAULT_EXTENSION_DESCRIPTION).unwrap_err(),
+            StrictParseError::UnknownFields(vec![
+                UnknownField {
+                    key: "dev".to_string(),
+                    field: "timout".to_string()
+                },
+                UnknownField {
+                    key: "other".to_string(),
+                    field: "arg".to_string()
+                },
+            ])
+        );
+
+        let malformed =
+            serde_json::json!({"bad": {"enabled": true, "type": "stdio", "name": "bad"}});
+        assert!(matches!(
+            parse_extensions_strict(malformed, DEFAULT_EXTENSION_DESCRIPTION),
+            Err(StrictParseError::Malformed { key, .. }) if key == "bad"
+        ));
+
+        // Declared fields skipped when empty are still known, however they're written.
+        let empty_defaults = serde_json::json!({
+            "dev": {"enabled": true, "type": "builtin", "name": "dev", "display_name": null, "timeout": 300, "disabled_tools": []}
+        });
+        assert!(parse_extensions_strict(empty_defaults, DEFAULT_EXTENSION_DESCRIPTION).is_ok());
+    }
+
+    #[test]
+    fn test_entry_fields_cover_entry_struct() {
+        let mut e = entry("dev", true);
+        e.load_order = Some(1);
+        e.updated_at = Some(1);
+        e.disabled_tools = vec!["shell".to_string()];
+        let entry_value = serde_json::to_value(&e).unwrap();
+        let config_value = serde_json::to_value(&e.config).unwrap();
+        let mut own: Vec<&str> = entry_value
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .filter(|field| !config_value.as_object().unwrap().contains_key(*field))
+            .collect();
+        own.sort();
+        let mut declared = ENTRY_FIELDS.to_vec();
+        declared.sort();
+        assert_eq!(own, declared);
+    }
+
+    #[test]
+    fn test_duplicate_names() {
+        let mut exts = extensions(&[("github", true), ("slack", true)]);
+        exts.insert("github-copy".to_string(), entry("github", false));
+        exts.insert("a-github".to_string(), entry("github", false));
+
+        let duplicates = duplicate_names(&exts);
+
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(
+            duplicates["github"],
+            vec![
+                "a-github".to_string(),
+                "github".to_string(),
+                "github-copy".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_group_patterns() {
+        let exts = extensions(&[("dev-git", true), ("dev-shell", true), ("docs", false)]);
+        let dev = group("dev", &["docs", "dev-shell"]).with_patterns(vec!["dev-*".into()]);
+
+        assert_eq!(
+            dev.resolve_members(&exts),
+            vec![
+                "docs".to_string(),
+                "dev-shell".to_string(),
+                "dev-git".to_string()
+            ]
+        );
+        assert!(dev.contains_key("dev-not-yet-added"));
+        assert!(!dev.contains_key("prod-shell"));
+        assert_eq!(compute_group_state(&dev, &exts), ExtensionGroupState::Mixed);
+
+        let only_pattern = group("dev", &[]).with_patterns(vec!["dev-?it".into(), "none-*".into()]);
+        assert_eq!(
+            only_pattern.resolve_members(&exts),
+            vec!["dev-git".to_string()]
+        );
+        assert_eq!(
+            compute_group_state(&only_pattern, &exts),
+            ExtensionGroupState::Enabled
+        );
+    }
+
+    #[test]
+    fn test_push_bounded() {
+        let mut history = VecDeque::new();
+        for i in 0..(UNDO_HISTORY_LIMIT + 3) {
+            push_bounded(&mut history, i, UNDO_HISTORY_LIMIT);
+        }
+
+        assert_eq!(history.len(), UNDO_HISTORY_LIMIT);
+        assert_eq!(history.front(), Some(&3));
+        assert_eq!(history.back(), Some(&(UNDO_HISTORY_LIMIT + 2)));
+    }
+
+    #[test]
+    fn test_config_snapshot_round_trip() {
+        let snapshot = ConfigSnapshot {
+            taken_at: 1_700_000_000,
+            extensions: Some(serde_json::json!({"dev": {"enabled": true}})),
+            groups: None,
+        };
+
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let restored: ConfigSnapshot = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored, snapshot);
+    }
+
+    #[test]
+    fn test_probe_command() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("server");
+        std::fs::write(&script, "#!/bin/sh\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+
+        let (status, _) = probe_command(script.to_str().unwrap());
+        assert_eq!(status, ExtensionProbeStatus::Ok);
+
+        let (status, _) = probe_command("definitely-not-a-real-goose-command");
+        assert_eq!(status, ExtensionProbeStatus::CommandNotFound);
+
+        let (status, _) = probe_extension(&ExtensionConfig::default());
+        assert_eq!(status, ExtensionProbeStatus::Skipped);
+    }
+
+    #[test]
+    fn test_probe_uri_within() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let uri = format!("http://localhost:{}", listener.local_addr().unwrap().port());
+
+        let (status, _) = probe_uri_within(&uri, PROBE_URI_TIMEOUT);
+        assert_eq!(status, ExtensionProbeStatus::Ok);
+
+        // Whether the lookup or the connect runs out first, nothing outlives the deadline.
+        let (status, _) = probe_uri_within(&uri, std::time::Duration::ZERO);
+        assert_eq!(status, ExtensionProbeStatus::Unreachable);
+    }
+
+    #[test]
+    fn test_merge_extension_maps() {
+        let base = extensions(&[("shared", false), ("base-only", true)]);
+        let mut overlay = extensions(&[("shared", true), ("overlay-only", false)]);
+        overlay.get_mut("shared").unwrap().config =
+            ExtensionConfig::stdio("shared", "new-cmd", "", DEFAULT_EXTENSION_TIMEOUT);
+
+        let replaced = merge_extension_maps(base.clone(), overlay.clone(), MergeMode::ReplaceEntry);
+        assert_eq!(replaced.len(), 3);
+        assert!(replaced["shared"].enabled);
+        assert!(replaced["base-only"].enabled);
+        assert!(!replaced["overlay-only"].enabled);
+
+        let preserved = merge_extension_maps(base, overlay, MergeMode::PreserveEnabled);
+        assert!(!preserved["shared"].enabled);
+        assert!(matches!(
+            &preserved["shared"].config,
+            ExtensionConfig::Stdio { cmd, .. } if cmd == "new-cmd"
+        ));
+        assert!(!preserved["overlay-only"].enabled);
+    }
+
+    #[test]
+    fn test_group_members_detailed() {
+        let exts = extensions(&[("a", true), ("b", false)]);
+        let members = group_members_detailed(&group("g", &["a", "b", "gone"]), &exts);
+
+        let summary: Vec<(&str, bool, bool)> = members
+            .iter()
+            .map(|m| (m.key.as_str(), m.entry.is_some(), m.enabled))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("a", true, true),
+                ("b", true, false),
+                ("gone", false, false)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sort_by_recency() {
+        let stamped = |name: &str, at: i64| {
+            set_test_now(Some(at));
+            let mut e = entry(name, true);
+            e.updated_at = Some(now_unix());
+            (name.to_string(), e)
+        };
+        let mut entries = vec![
+            ("never".to_string(), entry("never", true)),
+            stamped("old", 100),
+            stamped("new", 300),
+            stamped("also-old", 100),
+        ];
+        set_test_now(None);
+
+        sort_by_recency(&mut entries);
+
+        let keys: Vec<&str> = entries.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, vec!["new", "also-old", "old", "never"]);
+    }
+
+    #[test]
+    fn test_check_capacity() {
+        let mut exts = extensions(&[("a", true), ("b", true)]);
+        let (platform_key, def) = PLATFORM_EXTENSIONS.iter().next().unwrap();
+        exts.insert(platform_key.to_string(), platform_entry(def, true));
+
+        assert!(check_capacity(&exts, "c", None).is_ok());
+        assert!(check_capacity(&exts, "c", Some(3)).is_ok());
+        assert!(check_capacity(&exts, "a", Some(2)).is_ok());
+        assert!(matches!(
+            check_capacity(&exts, "c", Some(2)),
+            Err(ExtensionError::CapacityExceeded { limit: 2, .. })
+        ));
+
+        exts.remove(*platform_key);
+        assert!(check_capacity(&exts, platform_key, Some(2)).is_ok());
+    }
+
+    #[test]
+    fn test_check_total_capacity() {
+        let mut exts = extensions(&[("a", true), ("b", true), ("c", true)]);
+        let (platform_key, def) = PLATFORM_EXTENSIONS.iter().next().unwrap();
+        exts.insert(platform_key.to_string(), platform_entry(def, true));
+
+        assert!(check_total_capacity(&exts, None).is_ok());
+        assert!(check_total_capacity(&exts, Some(3)).is_ok());
+        assert!(matches!(
+            check_total_capacity(&exts, Some(2)),
+            Err(ExtensionError::CapacityExceeded { key, limit: 2 }) if key == "c"
+        ));
+    }
+
+    #[test]
+    fn test_redact_extension_secrets() {
+        let config = ExtensionConfig::Stdio {
+            name: "github".to_string(),
+            description: String::new(),
+            cmd: "github-mcp".to_string(),
+            args: Vec::new(),
+            envs: Envs::new(
+                [
+                    ("GITHUB_TOKEN", "ghp_123"),
+                    ("api_key", "abc"),
+                    ("GITHUB_HOST", "github.com"),
+                ]
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            ),
+            env_keys: Vec::new(),
+            timeout: Some(DEFAULT_EXTENSION_TIMEOUT),
+            bundled: None,
+            available_tools: Vec::new(),
+        };
+
+        let redacted = redact_extension_secrets(&config);
+        let ExtensionConfig::Stdio { envs, .. } = &redacted else {
+            panic!("variant changed");
+        };
+        let envs = envs.get_env();
+        assert_eq!(envs["GITHUB_TOKEN"], REDACTED_PLACEHOLDER);
+        assert_eq!(envs["api_key"], REDACTED_PLACEHOLDER);
+        assert_eq!(envs["GITHUB_HOST"], "github.com");
+
+        assert_eq!(redacted_secret_name(&config), None);
+        assert!(redacted_secret_name(&redacted).is_some());
+
+        let headers = redact_extension_secrets(&streamable_http(
+            "https://mcp.internal/mcp",
+            &[("X-Api-Key", "secret"), ("X-Team", "core")],
+        ));
+        assert_eq!(
+            redacted_secret_name(&headers),
+            Some("X-Api-Key".to_string())
+        );
+    }
+
+    #[test]
+    fn test_diff_keys() {
+        let before: HashSet<String> = ["a", "b", "c"].iter().map(|k| k.to_string()).collect();
+        let after: HashSet<String> = ["c", "d", "a"].iter().map(|k| k.to_string()).collect();
+
+        assert_eq!(
+            diff_keys(&before, &after),
+            (vec!["d".to_string()], vec!["b".to_string()])
+        );
+        assert_eq!(diff_keys(&after, &after), (vec![], vec![]));
+    }
+
+    #[test]
+    fn test_fill_missing_description() {
+        let mut missing = serde_json::json!({"name": "a"});
+        let mut null = serde_json::json!({"name": "a", "description": null});
+        let mut present = serde_json::json!({"name": "a", "description": "mine"});
+
+        fill_missing_description(&mut missing, "user-added via CLI");
+        fill_missing_description(&mut null, "");
+        fill_missing_description(&mut present, "user-added via CLI");
+
+        assert_eq!(missing["description"], "user-added via CLI");
+        assert_eq!(null["description"], "");
+        assert_eq!(present["description"], "mine");
+    }
+
+    #[test]
+    fn test_sort_by_name() {
+        let mut entries: Vec<(String, ExtensionEntry)> = vec![
+            ("zz".to_string(), entry("beta", true)),
+            ("b".to_string(), entry("Alpha", true)),
+            ("a".to_string(), entry("alpha", false)),
+            ("c".to_string(), entry("Gamma", true)),
+        ];
+
+        sort_by_name(&mut entries);
+
+        let keys: Vec<&str> = entries.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, vec!["a", "b", "zz", "c"]);
+    }
+
+    #[test]
+    fn test_invalid_extension_keys() {
+        let mut exts = extensions(&[("ok", true)]);
+        exts.insert(
+            "zero".to_string(),
+            entry_with(ExtensionConfig::stdio("zero", "cmd", "", 0u64), true),
+        );
+        exts.insert(
+            "bad-uri".to_string(),
+            entry_with(streamable_http("::", &[]), true),
+        );
+
+        exts.insert("Renamed".to_string(), entry("renamed", true));
+        exts.insert("".to_string(), entry(" ", true));
+
+        assert_eq!(
+            invalid_extension_keys(&exts),
+            vec![
+                "".to_string(),
+                "Renamed".to_string(),
+                "bad-uri".to_string(),
+                "zero".to_string()
+            ]
+        );
+        assert!(invalid_extension_keys(&extensions(&[("ok", true)])).is_empty());
+    }
+
+    #[test]
+    fn test_parse_counts_malformed_skipped() {
+        take_malformed_skipped();
+        let raw = serde_json::json!({
+            "good": {"enabled": true, "type": "builtin", "name": "good", "display_name": null, "timeout": 300},
+            "bad": { "enabled": "nope" },
+            "worse": 42
+        });
+
+        let parsed = parse_extensions_value(raw, "test", DEFAULT_EXTENSION_DESCRIPTION);
+
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(take_malformed_skipped(), 2);
+        assert_eq!(take_malformed_skipped(), 0);
+    }
+
+    #[test]
+    fn test_parse_mcp_manifest() {
+        let manifest = r#"{
+            "mcpServers": {
+                "Files": {"command": "npx", "args": ["-y", "server-files"], "env": {"ROOT": "/tmp"}},
+                "remote": {"url": "https://example.com/mcp", "headers": {"X-Token": "t"}},
+                "events": {"transport": {"type": "sse", "url": "https://example.com/sse"}},
+                "nothing": {"args": []},
+                "broken": {"url": "not a url"},
+                "odd": {"transport": "carrier-pigeon", "command": "coo"}
+            }
+        }"#;
+
+        let (entries, skipped) = parse_mcp_manifest(manifest, false, "imported").unwrap();
+
+        let keys: Vec<String> = entries.iter().map(|e| e.config.key()).collect();
+        assert_eq!(keys, vec!["files", "remote", "events"]);
+        assert!(entries.iter().all(|e| !e.enabled));
+        match &entries[0].config {
+            ExtensionConfig::Stdio {
+                cmd,
+                args,
+                envs,
+                description,
+                ..
+            } => {
+                assert_eq!(cmd, "npx");
+                assert_eq!(args, &vec!["-y".to_string(), "server-files".to_string()]);
+                assert_eq!(envs.get_env().get("ROOT").map(String::as_str), Some("/tmp"));
+                assert_eq!(description, "imported");
+            }
+            other => panic!("expected stdio, got {other:?}"),
+        }
+        assert!(matches!(
+            &entries[1].config,
+            ExtensionConfig::StreamableHttp { headers, .. } if headers.len() == 1
+        ));
+        assert!(matches!(entries[2].config, ExtensionConfig::Sse { .. }));
+
+        assert_eq!(skipped.len(), 3);
+        assert!(skipped[0].starts_with("nothing:"));
+        assert!(skipped[1].starts_with("broken:"));
+        assert!(skipped[2].starts_with("odd:"));
+    }
+
+    #[test]
+    fn test_parse_mcp_manifest_list_and_errors() {
+        let manifest = r#"[
+            {"name": "one", "command": "a"},
+            {"name": "One", "command": "b"},
+            {"command": "anonymous"}
+        ]"#;
+        let (entries, skipped) = parse_mcp_manifest(manifest, true, "").unwrap();
+        assert_eq!(entries.len(), 1);
+        assert!(entries[0].enabled);
+        assert_eq!(skipped.len(), 2);
+
+        assert!(matches!(
+            parse_mcp_manifest("{}", true, ""),
+            Err(ImportError::InvalidManifest(_))
+        ));
+        assert!(matches!(
+            parse_mcp_manifest("not json", true, ""),
+            Err(ImportError::InvalidManifest(_))
+        ));
+    }
+
+    #[test]
+    fn test_key_from_name_modes() {
+        assert_eq!(key_from_name(" Project Falcon ", false), "projectfalcon");
+        assert_eq!(key_from_name(" Project Falcon ", true), "ProjectFalcon");
+        assert_ne!(key_from_name("Falcon", true), key_from_name("falcon", true));
+        assert_eq!(name_to_key("Project Falcon"), "projectfalcon");
+    }
+
+    #[test]
+    fn test_key_conflict() {
+        let exts = extensions(&[("developer", true), ("memory", true)]);
+        let groups: HashMap<String, ExtensionGroup> = [
+            group("developer", &["memory"]),
+            group("research", &["memory"]),
+        ]
+        .into_iter()
+        .map(|g| (g.key(), g))
+        .collect();
+
+        assert_eq!(key_conflict("developer", &exts, &groups), KeyConflict::Both);
+        assert_eq!(
+            key_conflict("memory", &exts, &groups),
+            KeyConflict::Extension
+        );
+        assert_eq!(key_conflict("research", &exts, &groups), KeyConflict::Group);
+        assert_eq!(key_conflict("other", &exts, &groups), KeyConflict::None);
+    }
+
+    #[test]
+    fn test_apply_members_enabled_reports_each_member() {
+        let mut exts = extensions(&[("a", false), ("b", true), ("c", false)]);
+        let keys: Vec<String> = ["a", "b", "c", "gone"]
+            .iter()
+            .map(|k| k.to_string())
+            .collect();
+
+        set_test_now(Some(100));
+        let result = apply_members_enabled(&keys, &mut exts, true);
+        set_test_now(None);
+
+        assert_eq!(exts["a"].updated_at, Some(100));
+        assert_eq!(exts["b"].updated_at, None);
+        assert_eq!(result.changed, vec!["a".to_string(), "c".to_string()]);
+        assert_eq!(result.unchanged, vec!["b".to_string()]);
+        assert_eq!(result.missing, vec!["gone".to_string()]);
+        assert!(!result.is_complete());
+        assert!(exts.values().all(|e| e.enabled));
+
+        let again = apply_members_enabled(&keys[..3], &mut exts, true);
+        assert!(again.changed.is_empty());
+        assert!(again.is_complete());
+    }
+
+    #[test]
+    fn test_extension_index_lookups() {
+        let mut exts = extensions(&[("b-dev", true), ("memory", true)]);
+        let mut dup = entry("dev", true);
+        dup.config.set_name("b-dev");
+        exts.insert("a-dev".to_string(), dup);
+        for key in ["b-dev", "memory"] {
+            if let ExtensionConfig::Stdio {
+                available_tools, ..
+            } = &mut exts.get_mut(key).unwrap().config
+            {
+                *available_tools = vec!["search".to_string(), key.to_string()];
+            }
+        }
+
+        let index = ExtensionIndex::from_extensions(exts);
+
+        assert_eq!(index.len(), 3);
+        assert_eq!(index.key_for_name("b-dev"), Some("a-dev"));
+        assert_eq!(index.get_by_name("memory").unwrap().config.key(), "memory");
+        assert!(index.get_by_name("nope").is_none());
+        assert_eq!(
+            index.keys_for_tool("search"),
+            &["b-dev".to_string(), "memory".to_string()]
+        );
+        assert_eq!(index.keys_for_tool("memory"), &["memory".to_string()]);
+        assert!(index.keys_for_tool("unknown").is_empty());
+    }
+
+    #[test]
+    fn test_mask_group_preserves_member_states() {
+        let exts = extensions(&[("a", true), ("b", false), ("c", true)]);
+        let masked = group("g", &["a", "b", "gone"]).with_toggle_mode(GroupToggleMode::Mask);
+        let mut groups: HashMap<String, ExtensionGroup> = [(masked.key(), masked)].into();
+        let members = groups["g"].resolve_members(&exts);
+
+        let result = apply_group_mask("g", &members, &mut groups, &exts, false);
+
+        assert_eq!(result.changed, vec!["a".to_string()]);
+        assert_eq!(result.unchanged, vec!["b".to_string()]);
+        assert_eq!(result.missing, vec!["gone".to_string()]);
+        assert!(!is_effectively_enabled("a", &exts, &groups));
+        assert!(is_effectively_enabled("c", &exts, &groups));
+        assert!(exts["a"].enabled);
+        assert_eq!(
+            compute_group_state(&groups["g"], &exts),
+            ExtensionGroupState::Disabled
+        );
+
+        let result = apply_group_mask("g", &members, &mut groups, &exts, true);
+
+        assert_eq!(result.changed, vec!["a".to_string()]);
+        assert!(result.unchanged.is_empty());
+        assert_eq!(result.masked_off, vec!["b".to_string()]);
+        assert!(!result.is_complete());
+        assert!(is_effectively_enabled("a", &exts, &groups));
+        assert!(!is_effectively_enabled("b", &exts, &groups));
+        assert_eq!(
+            compute_group_state(&groups["g"], &exts),
+            ExtensionGroupState::Mixed
+        );
+    }
+
+    #[test]
+    fn test_group_toggle_fields_default_and_round_trip() {
+        let legacy: ExtensionGroup =
+            serde_json::from_value(serde_json::json!({"name": "g", "extension_keys": ["a"]}))
+                .unwrap();
+        assert_eq!(legacy.toggle_mode, GroupToggleMode::MutateMembers);
+        assert!(legacy.enabled);
+        assert_eq!(
+            serde_json::to_value(&legacy).unwrap(),
+            serde_json::json!({"name": "g", "extension_keys": ["a"]})
+        );
+
+        let mut masked = legacy.with_toggle_mode(GroupToggleMode::Mask);
+        masked.enabled = false;
+        let value = serde_json::to_value(&masked).unwrap();
+        assert_eq!(value["toggle_mode"], "mask");
+        assert_eq!(value["enabled"], false);
+    }
+
+    #[test]
+    fn test_ungrouped_extensions() {
+        let mut exts = extensions(&[("a", true), ("b", true), ("tool-x", true), ("c", false)]);
+        exts.insert(
+            "todo".to_string(),
+            entry_with(
+                ExtensionConfig::Platform {
+                    name: "todo".to_string(),
+                    description: String::new(),
+                    bundled: Some(true),
+                    available_tools: Vec::new(),
+                },
+                true,
+            ),
+        );
+        let groups: HashMap<String, ExtensionGroup> = [
+            group("g1", &["a", "missing"]),
+            group("g2", &[]).with_patterns(vec!["tool-*".to_string()]),
+        ]
+        .into_iter()
+        .map(|g| (g.key(), g))
+        .collect();
+
+        let keys = |entries: Vec<ExtensionEntry>| -> Vec<String> {
+            entries.iter().map(|e| e.config.key()).collect()
+        };
+        assert_eq!(
+            keys(ungrouped_extensions(exts.clone(), &groups, false)),
+            vec!["b", "c", "todo"]
+        );
+        assert_eq!(
+            keys(ungrouped_extensions(exts, &groups, true)),
+            vec!["b", "c"]
+        );
+    }
+
+    #[test]
+    fn test_insert_groups() {
+        let mut stored: HashMap<String, ExtensionGroup> =
+            [("old".to_string(), group("old", &["a"]))].into();
+
+        let keys = insert_groups(
+            &mut stored,
+            vec![
+                group("Dev Tools", &["a", "b", "a"]),
+                group("old", &["c"]),
+                group("devtools", &["z"]),
+            ],
+        );
+
+        assert_eq!(keys, vec!["devtools", "old", "devtools"]);
+        assert_eq!(stored.len(), 2);
+        assert_eq!(stored["devtools"].extension_keys, vec!["z".to_string()]);
+        assert_eq!(stored["old"].extension_keys, vec!["c".to_string()]);
+
+        insert_groups(&mut stored, vec![group("dedup", &["x", "y", "x", "y"])]);
+        assert_eq!(
+            stored["dedup"].extension_keys,
+            vec!["x".to_string(), "y".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_reconcile_platform_entries() {
+        let (platform_key, def) = PLATFORM_EXTENSIONS
+            .iter()
+            .min_by_key(|(key, _)| **key)
+            .unwrap();
+        let mut exts = extensions(&[("developer", true)]);
+        exts.insert(platform_key.to_string(), entry(platform_key, false));
+
+        assert_eq!(
+            reconcile_platform_entries(&mut exts),
+            vec![platform_key.to_string()]
+        );
+        let restored = &exts[*platform_key];
+        assert!(!restored.enabled);
+        assert!(matches!(
+            &restored.config,
+            ExtensionConfig::Platform { name, bundled: Some(true), .. } if name == def.name
+        ));
+        assert!(matches!(
+            exts["developer"].config,
+            ExtensionConfig::Stdio { .. }
+        ));
+
+        assert!(reconcile_platform_entries(&mut exts).is_empty());
+    }
+
+    #[test]
+    fn test_placeholders_for_missing_members() {
+        let mut exts = extensions(&[("a", true)]);
+        let template = group("template", &["a", "later", "later"]);
+
+        assert_eq!(add_placeholders(&template, &mut exts), vec!["later"]);
+        let placeholder = &exts["later"];
+        assert!(!placeholder.enabled);
+        assert!(is_placeholder(placeholder));
+        assert_eq!(placeholder.config.key(), "later");
+        assert_eq!(
+            compute_group_state(&template, &exts),
+            ExtensionGroupState::Mixed
+        );
+
+        let members = template.resolve_members(&exts);
+        let result = apply_members_enabled(&members, &mut exts, true);
+        assert_eq!(result.missing, vec!["later".to_string()]);
+        assert!(!exts["later"].enabled);
+
+        assert!(add_placeholders(&template, &mut exts).is_empty());
+    }
+
+    #[test]
+    fn test_group_membership() {
+        let exts = extensions(&[("a", true), ("b", false), ("c", true)]);
+        let groups: HashMap<String, ExtensionGroup> = [
+            group("on", &["a", "c"]),
+            group("mixed", &["a", "b", "gone"]),
+        ]
+        .into_iter()
+        .map(|g| (g.key(), g))
+        .collect();
+
+        let membership = group_membership(&exts, &groups);
+
+        assert_eq!(membership.len(), 3);
+        assert_eq!(
+            membership["a"],
+            vec![
+                ("mixed".to_string(), ExtensionGroupState::Mixed),
+                ("on".to_string(), ExtensionGroupState::Enabled),
+            ]
+        );
+        assert_eq!(
+            membership["b"],
+            vec![("mixed".to_string(), ExtensionGroupState::Mixed)]
+        );
+        assert_eq!(
+            membership["c"],
+            vec![("on".to_string(), ExtensionGroupState::Enabled)]
+        );
+        assert!(!membership.contains_key("gone"));
+    }
+
+    #[test]
+    fn test_parse_extensions_collecting() {
+        let raw = serde_json::json!({
+            "dev": {"enabled": true, "type": "builtin", "name": "dev", "display_name": null, "timeout": 300},
+            "bad": {"enabled": "yes"},
+            "worse": 7
+        });
+        let mut malformed = Vec::new();
+
+        let parsed = parse_extensions_collecting(raw, "test", "", &mut malformed).unwrap();
+
+        assert_eq!(parsed.len(), 1);
+        let keys: Vec<&str> = malformed.iter().map(|m| m.key.as_str()).collect();
+        assert_eq!(keys, vec!["bad", "worse"]);
+        assert_eq!(malformed[1].bad_json, "7");
+        assert!(!malformed[0].error.is_empty());
+
+        assert!(matches!(
+            parse_extensions_collecting(serde_json::json!([]), "test", "", &mut malformed),
+            Err(LoadError::NotAnObject(source)) if source == "test"
+        ));
+    }
+
+    #[test]
+    fn test_normalize_maps() {
+        let stdio = |name: &str| {
+            serde_json::json!({
+                "enabled": true, "type": "stdio", "name": name, "cmd": "c", "args": [], "timeout": 300
+            })
+        };
+        let mut described = stdio("memory");
+        described["description"] = "kept".into();
+        let raw = serde_json::json!({
+            "Dev Tools": stdio("Dev Tools"),
+            "memory": described,
+            "Taken": stdio("taken"),
+            "taken": stdio("taken"),
+        });
+        let Value::Object(raw) = raw else {
+            unreachable!()
+        };
+        let groups: HashMap<String, ExtensionGroup> = [group(
+            "g",
+            &["Dev Tools", "memory", "memory", "gone", "todo"],
+        )]
+        .into_iter()
+        .map(|mut g| {
+            g.exclusive_with = vec!["Other Group".to_string()];
+            (g.key(), g)
+        })
+        .collect();
+        let known: HashSet<String> = ["todo".to_string()].into();
+
+        let normalized = normalize_maps(raw, groups, &known, "filled");
+
+        assert!(normalized.extensions_changed);
+        assert!(normalized.groups_changed);
+        let mut keys: Vec<&String> = normalized.extensions.keys().collect();
+        keys.sort();
+        assert_eq!(keys, vec!["Taken", "devtools", "memory", "taken"]);
+        assert_eq!(normalized.extensions["devtools"]["description"], "filled");
+        assert_eq!(normalized.extensions["memory"]["description"], "kept");
+        assert_eq!(
+            normalized.groups["g"].extension_keys,
+            vec!["devtools", "memory", "todo"]
+        );
+        assert_eq!(normalized.groups["g"].exclusive_with, vec!["othergroup"]);
+        assert_eq!(
+            normalized.changes,
+            vec![
+                NormalizationChange::DescriptionFilled {
+                    key: "Dev Tools".to_string()
+                },
+                NormalizationChange::Rekeyed {
+                    from: "Dev Tools".to_string(),
+                    to: "devtools".to_string()
+                },
+                NormalizationChange::DescriptionFilled {
+                    key: "Taken".to_string()
+                },
+                NormalizationChange::RekeyConflict {
+                    from: "Taken".to_string(),
+                    to: "taken".to_string()
+                },
+                NormalizationChange::DescriptionFilled {
+                    key: "taken".to_string()
+                },
+                NormalizationChange::MemberRenamed {
+                    group: "g".to_string(),
+                    from: "Dev Tools".to_string(),
+                    to: "devtools".to_string()
+                },
+                NormalizationChange::OrphanedMemberPruned {
+                    group: "g".to_string(),
+                    member: "gone".to_string()
+                },
+                NormalizationChange::DuplicateMembersRemoved {
+                    group: "g".to_string(),
+                    members: vec!["memory".to_string()]
+                },
+            ]
+        );
+
+        let clean = normalize_maps(normalized.extensions, normalized.groups, &known, "filled");
+        assert_eq!(
+            clean.changes,
+            vec![NormalizationChange::RekeyConflict {
+                from: "Taken".to_string(),
+                to: "taken".to_string()
+            }]
+        );
+        assert!(!clean.extensions_changed && !clean.groups_changed);
+    }
+
+    #[test]
+    fn test_all_or_nothing_linked() {
+        let exts = extensions(&[
+            ("client", true),
+            ("server", true),
+            ("relay", true),
+            ("solo", true),
+        ]);
+        let groups: HashMap<String, ExtensionGroup> = [
+            group("transport", &["client", "server"]).with_all_or_nothing(true),
+            group("bridge", &["server", "relay"]).with_all_or_nothing(true),
+            group("loose", &["relay", "solo"]),
+        ]
+        .into_iter()
+        .map(|g| (g.key(), g))
+        .collect();
+
+        assert_eq!(
+            all_or_nothing_linked("client", &groups, &exts),
+            vec!["relay", "server"]
+        );
+        assert_eq!(
+            all_or_nothing_linked("relay", &groups, &exts),
+            vec!["client", "server"]
+        );
+        assert!(all_or_nothing_linked("solo", &groups, &exts).is_empty());
+    }
+
+    #[test]
+    fn test_set_enabled_linked() {
+        let mut exts = extensions(&[("client", false), ("server", false), ("solo", false)]);
+        let groups: HashMap<String, ExtensionGroup> =
+            [group("transport", &["client", "server", "later"]).with_all_or_nothing(true)]
+                .into_iter()
+                .map(|g| (g.key(), g))
+                .collect();
+
+        assert!(matches!(
+            set_enabled_linked("gone", true, &groups, &mut exts, 1),
+            Err(ExtensionError::NotFound(key)) if key == "gone"
+        ));
+
+        add_placeholders(&groups["transport"], &mut exts);
+        assert!(matches!(
+            set_enabled_linked("client", true, &groups, &mut exts, 1),
+            Err(ExtensionError::PlaceholderMembers { placeholders, .. })
+                if placeholders == vec!["later"]
+        ));
+        assert!(!exts["client"].enabled && !exts["server"].enabled);
+
+        exts.insert("later".to_string(), entry("later", false));
+        set_enabled_linked("client", true, &groups, &mut exts, 1).unwrap();
+        assert_eq!(
+            compute_group_state(&groups["transport"], &exts),
+            ExtensionGroupState::Enabled
+        );
+
+        exts.get_mut("later").unwrap().config = ExtensionConfig::unresolved("later");
+        set_enabled_linked("server", false, &groups, &mut exts, 2).unwrap();
+        assert_eq!(
+            compute_group_state(&groups["transport"], &exts),
+            ExtensionGroupState::Disabled
+        );
+        assert_eq!(exts["later"].updated_at, Some(2));
+
+        set_enabled_linked("solo", true, &groups, &mut exts, 3).unwrap();
+        assert!(exts["solo"].enabled);
+    }
+
+    #[test]
+    fn test_rank_fuzzy_matches() {
+        let exts = extensions(&[
+            ("developer", true),
+            ("devtools", true),
+            ("memory", true),
+            ("computercontroller", true),
+        ]);
+
+        let keys = |query: &str| -> Vec<String> {
+            rank_fuzzy_matches(query, exts.clone())
+                .into_iter()
+                .map(|(key, _)| key)
+                .collect()
+        };
+
+        assert_eq!(keys("dev"), vec!["devtools", "developer"]);
+        assert_eq!(keys("Develper"), vec!["developer"]);
+        assert_eq!(keys("memroy"), vec!["memory"]);
+        assert!(keys("browser").is_empty());
+        assert!(keys("").is_empty());
+    }
+
+    #[test]
+    fn test_bundled_default_entry() {
+        let developer = bundled_default_entry(DEFAULT_EXTENSION).unwrap();
+        assert!(developer.enabled);
+        assert_eq!(developer.config.key(), DEFAULT_EXTENSION);
+
+        let platform_key = *PLATFORM_EXTENSIONS.keys().next().unwrap();
+        let platform = bundled_default_entry(platform_key).unwrap();
+        assert!(matches!(platform.config, ExtensionConfig::Platform { .. }));
+
+        assert!(bundled_default_entry("custom").is_none());
+    }
+
+    #[test]
+    fn test_watched_state_diff() {
+        let before = WatchedState {
+            extensions: HashMap::from([
+                ("dev".to_string(), true),
+                ("memory".to_string(), true),
+                ("old".to_string(), false),
+            ]),
+            groups: HashSet::from(["coding".to_string()]),
+        };
+        let after = WatchedState {
+            extensions: HashMap::from([
+                ("dev".to_string(), true),
+                ("memory".to_string(), false),
+                ("new".to_string(), true),
+            ]),
+            groups: HashSet::from(["writing".to_string()]),
+        };
+
+        assert_eq!(
+            before.diff(&after),
+            vec![
+                ExtensionChangeEvent::Added {
+                    key: "new".to_string()
+                },
+                ExtensionChangeEvent::Removed {
+                    key: "old".to_string()
+                },
+                ExtensionChangeEvent::Toggled {
+                    key: "memory".to_string(),
+                    enabled: false
+                },
+                ExtensionChangeEvent::GroupAdded {
+                    key: "writing".to_string()
+                },
+                ExtensionChangeEvent::GroupRemoved {
+                    key: "coding".to_string()
+                },
+            ]
+        );
+        assert!(after.diff(&after).is_empty());
+    }
+
+    #[test]
+    fn test_simulate_group_ops() {
+        let exts = extensions(&[("dev", false), ("memory", false), ("web", true)]);
+        let groups = HashMap::from([
+            ("coding".to_string(), group("coding", &["dev", "memory"])),
+            (
+                "browsing".to_string(),
+                group("browsing", &["web", "memory"]),
+            ),
+        ]);
+
+        let result = simulate_group_ops(
+            &[
+                GroupOp::Enable("coding".to_string()),
+                GroupOp::Disable("browsing".to_string()),
+                GroupOp::Enable("unknown".to_string()),
+            ],
+            groups,
+            exts,
+        );
+
+        assert_eq!(
+            result,
+            HashMap::from([
+                ("dev".to_string(), true),
+                ("memory".to_string(), false),
+                ("web".to_string(), false),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_keys_in_state() {
+        let exts = extensions(&[("dev", true), ("memory", false), ("web", true)]);
+        let groups = HashMap::from([
+            ("coding".to_string(), group("coding", &["dev", "web"])),
+            ("mixed".to_string(), group("mixed", &["dev", "memory"])),
+            ("quiet".to_string(), group("quiet", &["memory"])),
+            (
+                "also_mixed".to_string(),
+                group("also_mixed", &["web", "memory"]),
+            ),
+        ]);
+        let states = group_states(&groups, &exts);
+
+        assert_eq!(
+            keys_in_state(states.clone(), ExtensionGroupState::Mixed),
+            vec!["also_mixed", "mixed"]
+        );
+        assert_eq!(
+            keys_in_state(states.clone(), ExtensionGroupState::Enabled),
+            vec!["coding"]
+        );
+        assert_eq!(
+            keys_in_state(states, ExtensionGroupState::Disabled),
+            vec!["quiet"]
+        );
+    }
+
+    #[test]
+    fn test_extensions_toml_round_trip() {
+        let mut exts = extensions(&[("dev", true), ("memory", false)]);
+        exts.insert(
+            "gateway".to_string(),
+            ExtensionEntry {
+                load_order: Some(2),
+                ..entry_with(
+                    streamable_http("https://example.com/mcp", &[("Authorization", "Bearer x")]),
+                    true,
+                )
+            },
+        );
+
+        let toml = map_to_toml(&exts).unwrap();
+        assert!(toml.find("[dev]").unwrap() < toml.find("[gateway]").unwrap());
+
+        let parsed = extensions_from_toml(&toml).unwrap();
+        assert_eq!(
+            serde_json::to_value(&parsed).unwrap(),
+            serde_json::to_value(&exts).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_extensions_from_toml_rejects_invalid() {
+        assert!(matches!(
+            extensions_from_toml("[dev]\nenabled = "),
+            Err(SerializeError::Deserialize(_))
+        ));
+        assert!(matches!(
+            extensions_from_toml("[dev]\nenabled = true\n"),
+            Err(SerializeError::Deserialize(_))
+        ));
+    }
+
+    #[test]
+    fn test_apply_runtime_overrides() {
+        let mut exts = extensions(&[("dev", false), ("memory", true)]);
+        let overrides = HashMap::from([
+            (
+                "dev".to_string(),
+                RuntimeOverride {
+                    enabled: Some(true),
+                    timeout: Some(30),
+                },
+            ),
+            (
+                "missing".to_string(),
+                RuntimeOverride {
+                    enabled: Some(true),
+                    timeout: None,
+                },
+            ),
+        ]);
+
+        apply_runtime_overrides(&mut exts, &overrides);
+
+        assert!(exts["dev"].enabled);
+        assert_eq!(extension_timeout(&exts["dev"].config), Some(30));
+        assert!(exts["memory"].enabled);
+        assert_eq!(
+            extension_timeout(&exts["memory"].config),
+            Some(DEFAULT_EXTENSION_TIMEOUT)
+        );
+        assert!(!exts.contains_key("missing"));
+    }
+
+    #[test]
+    fn test_layer_extensions() {
+        let global = extensions(&[("dev", true), ("memory", true)]);
+        let project = extensions(&[("dev", false)]);
+        let overrides = HashMap::from([(
+            "memory".to_string(),
+            RuntimeOverride {
+                enabled: Some(false),
+                timeout: None,
+            },
+        )]);
+
+        let layered = layer_extensions(global, project, &overrides);
+
+        assert!(!layered["dev"].enabled);
+        assert!(!layered["memory"].enabled);
+    }
+
+    #[test]
+    fn test_normalize_group_members() {
+        let exts = extensions(&[("dev", true), ("memory", true)]);
+        let mut coding = group("coding", &[" Dev", "MEMORY", "memory", "de v"]);
+        coding.exclusive_with = vec!["Web Dev".to_string()];
+        assert_eq!(missing_group_members(&coding, &exts).len(), 3);
+
+        normalize_group_members(&mut coding);
+
+        assert_eq!(coding.extension_keys, vec!["dev", "memory"]);
+        assert_eq!(coding.exclusive_with, vec!["webdev"]);
+        assert!(missing_group_members(&coding, &exts).is_empty());
+        assert_eq!(coding.resolve_members(&exts), vec!["dev", "memory"]);
+        assert_eq!(
+            compute_group_state(&coding, &exts),
+            ExtensionGroupState::Enabled
+        );
+    }
+
+    #[test]
+    fn test_rename_group_updates_exclusive_with() {
+        let mut groups: HashMap<String, ExtensionGroup> = [
+            (
+                "bash".to_string(),
+                group("bash", &[]).with_exclusive_with(vec!["Zsh".into()]),
+            ),
+            (
+                "zsh".to_string(),
+                group("zsh", &[]).with_exclusive_with(vec!["bash".into()]),
+            ),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(groups["bash"].exclusive_with, vec!["zsh"]);
+
+        rename_group(&mut groups, "zsh", "Z Shell").unwrap();
+
+        assert!(!groups.contains_key("zsh"));
+        assert_eq!(groups["zshell"].name, "Z Shell");
+        assert_eq!(groups["zshell"].exclusive_with, vec!["bash"]);
+        assert_eq!(groups["bash"].exclusive_with, vec!["zshell"]);
+        assert!(matches!(
+            rename_group(&mut groups, "bash", "zshell"),
+            Err(ExtensionGroupError::AlreadyExists(_))
+        ));
+    }
+
+    #[test]
+    fn test_add_and_remove_member_use_canonical_keys() {
+        let mut members = Vec::new();
+        assert!(add_member(&mut members, "Dev"));
+        assert!(!add_member(&mut members, "dev"));
+        assert!(add_member(&mut members, "Web Search"));
+        assert_eq!(members, vec!["dev", "websearch"]);
+
+        assert!(remove_member(&mut members, "Web Search"));
+        assert!(!remove_member(&mut members, "websearch"));
+        assert_eq!(members, vec!["dev"]);
+    }
+
+    #[test]
+    fn test_remove_keys() {
+        let keys: Vec<String> = ["dev", "gone", "memory", "dev"]
+            .iter()
+            .map(|k| k.to_string())
+            .collect();
+        let groups = HashMap::from([
+            ("coding".to_string(), group("coding", &["dev", "web"])),
+            ("other".to_string(), group("other", &["web"])),
+        ]);
+
+        let mut exts = extensions(&[("dev", true), ("memory", false), ("web", true)]);
+        let mut kept_groups = groups.clone();
+        let (removed, missing) = remove_keys(&keys, &mut exts, &mut kept_groups, false);
+        assert_eq!(removed.extensions, vec!["dev", "memory"]);
+        assert!(!removed.groups_changed);
+        assert_eq!(missing, vec!["gone"]);
+        assert_eq!(exts.keys().collect::<Vec<_>>(), vec!["web"]);
+        assert_eq!(kept_groups, groups);
+
+        let mut exts = extensions(&[("dev", true), ("memory", false), ("web", true)]);
+        let mut cascaded = groups;
+        let (removed, _) = remove_keys(&keys, &mut exts, &mut cascaded, true);
+        assert!(removed.groups_changed);
+        assert_eq!(cascaded["coding"].extension_keys, vec!["web"]);
+        assert_eq!(cascaded["other"].extension_keys, vec!["web"]);
+    }
+
+    #[test]
+    fn test_missing_platform_keys() {
+        let mut all: Vec<String> = PLATFORM_EXTENSIONS.keys().map(|k| k.to_string()).collect();
+        all.sort();
+        assert_eq!(missing_platform_keys(&HashMap::new()), all);
+
+        let mut exts = extensions(&[("dev", true)]);
+        let stored = all[0].clone();
+        exts.insert(
+            stored.clone(),
+            platform_entry(&PLATFORM_EXTENSIONS[stored.as_str()], false),
+        );
+        let missing = missing_platform_keys(&exts);
+        assert_eq!(missing.len(), all.len() - 1);
+        assert!(!missing.contains(&stored));
+    }
+
+    #[test]
+    fn test_extensions_page() {
+        let exts = extensions(&[
+            ("alpha", false),
+            ("bravo", true),
+            ("charlie", false),
+            ("delta", true),
+            ("echo", true),
+        ]);
+        let names = |page: ExtensionPage| -> Vec<String> {
+            page.entries.iter().map(|e| e.config.name()).collect()
+        };
+
+        let first = extensions_page(exts.clone(), 0, 2, ExtensionSort::Name);
+        assert_eq!(first.total, 5);
+        assert_eq!(names(first), vec!["alpha", "bravo"]);
+        assert_eq!(
+            names(extensions_page(exts.clone(), 2, 2, ExtensionSort::Name)),
+            vec!["charlie", "delta"]
+        );
+        assert_eq!(
+            names(extensions_page(
+                exts.clone(),
+                0,
+                4,
+                ExtensionSort::EnabledFirst
+            )),
+            vec!["bravo", "delta", "echo", "alpha"]
+        );
+
+        let past_end = extensions_page(exts, 10, 2, ExtensionSort::Recency);
+        assert!(past_end.entries.is_empty());
+        assert_eq!(past_end.total, 5);
+    }
+
+    #[test]
+    fn test_is_valid_extension_name() {
+        assert!(is_valid_extension_name("developer"));
+        assert!(is_valid_extension_name(" Computer Controller "));
+        assert!(!is_valid_extension_name(""));
+        assert!(!is_valid_extension_name("   "));
+        assert!(!is_valid_extension_name("\t\n "));
+        assert!(key_from_name(" \t ", false).is_empty());
+    }
+
+    #[test]
+    fn test_dedupe_by_name() {
+        let mut stale = entry("github", true);
+        stale.updated_at = Some(100);
+        let mut fresh = entry("github", true);
+        fresh.updated_at = Some(200);
+        let exts: HashMap<String, ExtensionEntry> = [
+            ("tools".to_string(), entry("tools", false)),
+            ("tools2".to_string(), entry("tools", true)),
+            ("github".to_string(), stale),
+            ("github-copy".to_string(), fresh),
+            ("solo".to_string(), entry("solo", false)),
+            ("twin-b".to_string(), entry("twin", false)),
+            ("twin-a".to_string(), entry("twin", false)),
+        ]
+        .into_iter()
+        .collect();
+
+        let (kept, dropped) = dedupe_by_name(exts);
+        let mut kept_keys: Vec<&str> = kept.keys().map(String::as_str).collect();
+        kept_keys.sort();
+        assert_eq!(kept_keys, vec!["github-copy", "solo", "tools2", "twin-a"]);
+        assert_eq!(
+            dropped,
+            vec![
+                ("github".to_string(), "github-copy".to_string()),
+                ("tools".to_string(), "tools2".to_string()),
+                ("twin-b".to_string(), "twin-a".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_toggle_disabled_tool() {
+        let mut tools = Vec::new();
+        assert!(toggle_disabled_tool(&mut tools, "shell", true));
+        assert!(!toggle_disabled_tool(&mut tools, "shell", true));
+        assert!(toggle_disabled_tool(&mut tools, "text_editor", true));
+        assert_eq!(tools, vec!["shell", "text_editor"]);
+        assert!(toggle_disabled_tool(&mut tools, "shell", false));
+        assert!(!toggle_disabled_tool(&mut tools, "shell", false));
+        assert_eq!(tools, vec!["text_editor"]);
+
+        let mut e = entry("developer", true);
+        assert!(!serde_json::to_value(&e)
+            .unwrap()
+            .as_object()
+            .unwrap()
+            .contains_key("disabled_tools"));
+        e.disabled_tools = tools;
+        let parsed: ExtensionEntry =
+            serde_json::from_value(serde_json::to_value(&e).unwrap()).unwrap();
+        assert_eq!(parsed.disabled_tools, vec!["text_editor"]);
+    }
+
+    #[test]
+    fn test_config_problems() {
+        let mut exts = extensions(&[("dev", true), ("github", true)]);
+        exts.insert("GitHub2".to_string(), entry("github", false));
+        exts.insert("slow".to_string(), {
+            let mut e = entry("slow", true);
+            set_extension_timeout(&mut e.config, MAX_EXTENSION_TIMEOUT + 1);
+            e
+        });
+        let groups: HashMap<String, ExtensionGroup> = [
+            (
+                "coding".to_string(),
+                group("coding", &["dev", "gone", "local"]),
+            ),
+            ("dev".to_string(), group("dev", &[])),
+        ]
+        .into_iter()
+        .collect();
+        let overlay = HashSet::from(["local".to_string()]);
+
+        let problems = config_problems(&exts, &groups, &overlay, false);
+        let find = |kind: &ConfigProblemKind| problems.iter().find(|p| &p.kind == kind);
+
+        let invalid = problems
+            .iter()
+            .find(|p| matches!(p.kind, ConfigProblemKind::InvalidConfig { .. }))
+            .unwrap();
+        assert_eq!(invalid.key.as_deref(), Some("slow"));
+        assert_eq!(invalid.severity, ProblemSeverity::Error);
+
+        let unnormalized = find(&ConfigProblemKind::UnnormalizedKey {
+            expected: "github".to_string(),
+        })
+        .unwrap();
+        assert_eq!(unnormalized.key.as_deref(), Some("GitHub2"));
+        assert_eq!(unnormalized.fix, Some(SuggestedFix::NormalizeConfig));
+
+        let duplicates = ConfigProblemKind::DuplicateName {
+            name: "github".to_string(),
+            keys: vec!["GitHub2".to_string(), "github".to_string()],
+        };
+        assert_eq!(problems.iter().filter(|p| p.kind == duplicates).count(), 2);
+
+        let collision = find(&ConfigProblemKind::KeyCollision).unwrap();
+        assert_eq!(collision.group.as_deref(), Some("dev"));
+
+        let orphans: Vec<&ConfigProblem> = problems
+            .iter()
+            .filter(|p| matches!(p.kind, ConfigProblemKind::OrphanedMember { .. }))
+            .collect();
+        assert_eq!(orphans.len(), 1);
+        assert_eq!(
+            orphans[0].fix,
+            Some(SuggestedFix::RemoveGroupMember {
+                group: "coding".to_string(),
+                member: "gone".to_string(),
+            })
+        );
+
+        assert!(find(&ConfigProblemKind::MissingPlatformExtension).is_none());
+        let with_platform = config_problems(&exts, &groups, &overlay, true);
+        assert_eq!(
+            with_platform
+                .iter()
+                .filter(|p| p.kind == ConfigProblemKind::MissingPlatformExtension)
+                .count(),
+            PLATFORM_EXTENSIONS.len()
+        );
+
+        assert!(config_problems(
+            &extensions(&[("dev", true)]),
+            &HashMap::new(),
+            &overlay,
+            false
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_key_for_name() {
+        let mut exts = extensions(&[("dev", true)]);
+        exts.insert("a-dev".to_string(), entry("dev", false));
+        assert_eq!(key_for_name(&exts, "dev").as_deref(), Some("a-dev"));
+        assert_eq!(key_for_name(&exts, "missing"), None);
+    }
+
+    #[test]
+    fn test_watch_paths_sees_direct_edits() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.yaml");
+        std::fs::write(&path, "extensions: {}\n").unwrap();
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let handle = watch_paths(
+            vec![path.clone()],
+            std::time::Duration::from_millis(10),
+            move || {
+                let _ = tx.send(());
+            },
+        );
+
+        std::fs::write(&path, "extensions: {}\nextension_groups: {}\n").unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1))
+            .unwrap();
+        rx.recv_timeout(std::time::Duration::from_secs(5))
+            .expect("edit was not noticed");
+
+        drop(handle);
+        while rx.try_recv().is_ok() {}
+        assert_eq!(
+            rx.try_recv(),
+            Err(std::sync::mpsc::TryRecvError::Disconnected)
+        );
+    }
+
+    #[test]
+    fn test_group_state_cache_dropped_on_direct_edit() {
+        // A cache of its own, so tests running alongside neither see nor disturb it.
+        let group_states: &'static Mutex<GroupStateCache> =
+            Box::leak(Box::new(Mutex::new(GroupStateCache::new())));
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.yaml");
+        std::fs::write(&path, "extension_groups: {}\n").unwrap();
+        let read_path = path.clone();
+        let read_state = move || WatchedState {
+            extensions: HashMap::new(),
+            groups: read_yaml_value(&read_path)
+                .ok()
+                .and_then(|config| {
+                    config
+                        .get(EXTENSION_GROUPS_CONFIG_KEY)?
+                        .as_object()
+                        .map(|groups| groups.keys().cloned().collect())
+                })
+                .unwrap_or_default(),
+        };
+        let (tx, rx) = std::sync::mpsc::channel();
+        let handle = watch_config(
+            vec![path.clone()],
+            std::time::Duration::from_millis(10),
+            read_state,
+            group_states,
+            move |event| {
+                let _ = tx.send(event);
+            },
+        );
+        group_states
+            .lock()
+            .unwrap()
+            .states
+            .put("coding".to_string(), ExtensionGroupState::Enabled);
+
+        std::fs::write(&path, "extension_groups:\n  coding:\n    name: coding\n").unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1))
+            .unwrap();
+        let event = rx
+            .recv_timeout(std::time::Duration::from_secs(5))
+            .expect("edit was not noticed");
+        drop(handle);
+
+        assert_eq!(
+            event,
+            ExtensionChangeEvent::GroupAdded {
+                key: "coding".to_string()
+            }
+        );
+        assert!(group_states.lock().unwrap().states.is_empty());
+    }
 }
@@ -1156,6 +1156,9 @@ mod extension_manager_tests {
         // Set it as disabled initially so tests can enable it
         let todo_extension_entry = ExtensionEntry {
             enabled: false,
+            load_order: None,
+            updated_at: None,
+            disabled_tools: Vec::new(),
             config: ExtensionConfig::Platform {
                 name: "todo".to_string(),
                 description:
@@ -1165,7 +1168,7 @@ mod extension_manager_tests {
                 available_tools: vec![],
             },
         };
-        set_extension(todo_extension_entry);
+        set_extension(todo_extension_entry).expect("failed to save todo extension");
 
         let agent = Agent::new();
 